@@ -1,42 +1,72 @@
-use std::time::Duration;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use chess::{BoardStatus, ChessMove, Color, File, MoveGen, Piece, Rank, Square};
 use eframe::egui;
 use super::ChessApp;
-use crate::uiboard::detect_clicked_square;
-use crate::best_move::best_move;
+use crate::uiboard::{detect_clicked_square, piece_image_index, square_to_row_col};
+use rust_chess_engine::pgn::{engine_comment, PgnMove};
+use rust_chess_engine::{captured_piece, draw_reason, parse_fen, DrawReason, Engine, FenPosition, PgnGame, PgnResult, SearchLimits, SearchOptions, SearchResult, SearchStats};
+use crate::clock::{Clocks, TIME_CONTROLS};
+use crate::{SearchMode, SideChoice};
 
 pub enum TurnStates {
     PieceSelection,
     SquareSelection,
+    PromotionSelection,
     OpponentMoves,
+    OpponentThinking,
     Checkmate,
-    Stalemate
+    Draw(DrawReason),
+    // the color whose flag fell
+    OutOfTime(Color)
+}
+
+// the choices offered in the promotion popup, from the promotion square inwards
+const PROMOTION_PIECES: [Piece; 4] = [Piece::Queen, Piece::Knight, Piece::Rook, Piece::Bishop];
+
+// what the search thread sends back to the gui once it is done
+pub struct OpponentReply {
+    result: SearchResult,
+    stats: SearchStats,
 }
 
 // implement functions that handle player action
 impl ChessApp {
-    pub fn action_manager(&mut self, ui: &mut egui::Ui, squares: &[[egui::Rect; 8]; 8]){
+    pub fn action_manager(&mut self, ui: &mut egui::Ui, squares: &[[egui::Rect; 8]; 8], piece_images: &[egui::Image<'_>]){
+        if !self.game_is_over {
+            self.check_flag();
+        }
+
         match self.turn_state {
             TurnStates::PieceSelection => self.select_piece(ui, squares),
             TurnStates::SquareSelection => self.select_square(ui, squares),
-            TurnStates::OpponentMoves => self.move_opponent(),
+            TurnStates::PromotionSelection => self.select_promotion(ui, squares, piece_images),
+            TurnStates::OpponentMoves => self.move_opponent(ui.ctx()),
+            TurnStates::OpponentThinking => self.receive_opponent_move(),
             TurnStates::Checkmate => self.game_is_over = true,
-            TurnStates::Stalemate => self.game_is_over = true,
+            TurnStates::Draw(_) => self.game_is_over = true,
+            TurnStates::OutOfTime(_) => self.game_is_over = true,
         }
     }
 
+    #[allow(clippy::single_match)]
     fn select_piece(&mut self, ui: &mut egui::Ui, squares: &[[egui::Rect; 8]; 8]) {
         
-        if self.board.status() == BoardStatus::Ongoing{
-            match detect_clicked_square(ui, squares) {
+        if self.game_continues() {
+            match detect_clicked_square(ui, squares, self.white_at_bottom()) {
                 Some((row, col)) => {
                     let clicked_square = row_col_to_square(row, col);
 
-                    if self.board.piece_on(clicked_square).is_some() {
+                    if self.position.board.piece_on(clicked_square).is_some() {
                         self.source_square = Some(clicked_square);
 
-                        let legal_moves = MoveGen::new_legal(&self.board);
+                        let legal_moves = MoveGen::new_legal(&self.position.board);
             
                         self.legal_moves_from_source = legal_moves
                             .filter(|m| m.get_source() == clicked_square)
@@ -52,27 +82,24 @@ impl ChessApp {
                 },
                 None => ()
             }
-        } else if self.board.status() == BoardStatus::Checkmate {
-            self.checkmate();
-        } else {
-            self.turn_state = TurnStates::Stalemate;
-    }
+        }
         
     }
 
+    #[allow(clippy::single_match)]
     fn select_square(&mut self, ui: &mut egui::Ui, squares: &[[egui::Rect; 8]; 8]) {
         
-        if self.board.status() == BoardStatus::Ongoing{
-            match detect_clicked_square(ui, squares) {
+        if self.game_continues() {
+            match detect_clicked_square(ui, squares, self.white_at_bottom()) {
                 Some((row, col)) => {
                     let clicked_square = row_col_to_square(row, col);
     
                     let mut possible_move = ChessMove::new(self.source_square.unwrap(), clicked_square, None);
     
-                    let piece = self.board.piece_on(self.source_square.unwrap());
+                    let piece = self.position.board.piece_on(self.source_square.unwrap());
                     if let Some(piece) = piece {
                         if piece == Piece::Pawn {
-                            let promotion_rank = if self.board.side_to_move() == Color::White {
+                            let promotion_rank = if self.position.board.side_to_move() == Color::White {
                                 Rank::Eighth
                             } else {
                                 Rank::First
@@ -84,19 +111,21 @@ impl ChessApp {
                         }
                     }
 
-                    if self.legal_moves_from_source.contains(&possible_move) {
-                        
-                        if self.board.piece_on(clicked_square).is_some(){
-                            self.black_slain_pieces.push(self.board.piece_on(clicked_square).unwrap());
-                        } 
-
-                        self.board = self.board.make_move_new(possible_move);
+                    if self.legal_moves_from_source.contains(&possible_move) && possible_move.get_promotion().is_some() {
+                        // the piece is picked in a popup before the move is played
+                        self.pending_promotion = Some((possible_move.get_source(), clicked_square));
+                        self.legal_moves_from_source = Vec::new();
+                        self.turn_state = TurnStates::PromotionSelection;
+                    } else if self.legal_moves_from_source.contains(&possible_move) {
+                        // a new move makes the undone ones unreachable
+                        self.undone_moves.clear();
+                        self.play_turn(possible_move, None);
                         self.turn_state = TurnStates::OpponentMoves;
                     } else {
-                        if self.board.piece_on(clicked_square).is_some() {
+                        if self.position.board.piece_on(clicked_square).is_some() {
                             self.source_square = Some(clicked_square);
         
-                            let legal_moves = MoveGen::new_legal(&self.board);
+                            let legal_moves = MoveGen::new_legal(&self.position.board);
                 
                             self.legal_moves_from_source = legal_moves
                                 .filter(|m| m.get_source() == clicked_square)
@@ -110,54 +139,347 @@ impl ChessApp {
                 },
                 None => ()
             }  
-        } else if self.board.status() == BoardStatus::Checkmate {
-            self.checkmate();
-        } else {
-            self.turn_state = TurnStates::Stalemate;
         }
         
     }
 
-    fn move_opponent(&mut self) {
+    // a column of the four pieces over the promotion square; clicking anywhere else cancels the move
+    fn select_promotion(&mut self, ui: &mut egui::Ui, squares: &[[egui::Rect; 8]; 8], piece_images: &[egui::Image<'_>]) {
+        let (source, dest) = match self.pending_promotion {
+            Some(promotion) => promotion,
+            None => {
+                self.turn_state = TurnStates::PieceSelection;
+                return;
+            },
+        };
+
+        let color = self.position.board.side_to_move();
+        let (row, col) = square_to_row_col(dest, self.white_at_bottom());
+        let mut choices = Vec::new();
+
+        for (i, piece) in PROMOTION_PIECES.into_iter().enumerate() {
+            // the column grows towards the middle of the board
+            let choice_row = if row == 0 { row + i } else { row - i };
+            let rect = squares[choice_row][col];
+
+            ui.painter().rect_filled(rect, 4.0, egui::Color32::from_rgb(185, 180, 199));
+            ui.put(rect, piece_images[piece_image_index(piece, color)].clone());
+            choices.push((rect, piece));
+        }
+
+        if !ui.input(|i| i.pointer.primary_pressed()) {
+            return;
+        }
+
+        let pointer = ui.input(|i| i.pointer.interact_pos());
+        let chosen = choices
+            .into_iter()
+            .find(|(rect, _)| pointer.is_some_and(|pos| rect.contains(pos)))
+            .map(|(_, piece)| piece);
+
+        self.pending_promotion = None;
+        self.source_square = None;
+
+        match chosen {
+            Some(piece) => {
+                self.undone_moves.clear();
+                self.play_turn(ChessMove::new(source, dest, Some(piece)), None);
+                self.turn_state = TurnStates::OpponentMoves;
+            },
+            None => self.turn_state = TurnStates::PieceSelection,
+        }
+    }
+
+    fn move_opponent(&mut self, ctx: &egui::Context) {
         
-        if self.board.status() == BoardStatus::Ongoing{
-            let (_, best_move) = best_move(
-                &self.pruning,
-                &self.board,
-                self.depth,
-                self.board.side_to_move() == Color::White,
-                &mut self.count,
-                &mut self.time_elapsed,
-            );
-    
-            match best_move {
-                Some(best_move) => {
-                    
-                    if self.board.piece_on(best_move.get_dest()).is_some() {
-                        self.white_slain_pieces.push(self.board.piece_on(best_move.get_dest()).unwrap());
-                    }
-                    
-                    self.board = self.board.make_move_new(best_move);   
+        if self.game_continues() {
+            let (sender, receiver) = mpsc::channel();
+            let position = self.position;
+            // the current position is the last one in the history, the engine wants the ones before it
+            let history = self.position_history[..self.position_history.len() - 1].to_vec();
+            // with a clock the engine budgets its own time, the search settings are for untimed games
+            let limits = match (&self.clocks, self.search_mode) {
+                (Some(clocks), _) => {
+                    let color = self.position.board.side_to_move();
+                    SearchLimits::from_clock(clocks.time_left(color), clocks.control.increment, clocks.moves_to_go(color))
                 },
-                None => (),
+                (None, SearchMode::FixedDepth) => SearchLimits::fixed_depth(self.depth),
+                (None, SearchMode::FixedTime) => SearchLimits::fixed_time(Duration::from_millis(self.move_time_ms)),
+            };
+            let options = SearchOptions {
+                pruning: self.pruning,
+                move_ordering: self.move_ordering,
+                contempt: self.contempt,
+            };
+            let ctx = ctx.clone();
+
+            self.stop_search = Arc::new(AtomicBool::new(false));
+            let stop = Arc::clone(&self.stop_search);
+            let engine = Arc::clone(&self.engine);
+            let hash_mb = self.hash_mb;
+
+            // the search runs on its own thread so the window keeps repainting while it thinks
+            thread::spawn(move || {
+                let mut engine = engine.lock().unwrap();
+
+                // the settings are applied here so the gui never waits on the lock
+                engine.set_hash_size(hash_mb);
+                engine.set_options(options);
+                engine.set_game(position, &history);
+
+                let result = engine.search(&limits, &stop);
+                let stats = engine.last_stats();
+
+                // the receiver is gone if the game was reset in the meantime
+                let _ = sender.send(OpponentReply { result, stats });
+                ctx.request_repaint();
+            });
+
+            self.opponent_reply = Some(receiver);
+            self.turn_state = TurnStates::OpponentThinking;
+
+        }
+     
+    }
+
+    fn receive_opponent_move(&mut self) {
+        let reply = match &self.opponent_reply {
+            Some(receiver) => match receiver.try_recv() {
+                Ok(reply) => reply,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => OpponentReply {
+                    result: SearchResult::default(),
+                    stats: SearchStats::default(),
+                },
+            },
+            None => return,
+        };
+
+        self.opponent_reply = None;
+        self.stats = reply.stats;
+        self.principal_variation = reply.result.pv;
+
+        if let Some(best_move) = reply.result.best_move {
+            let comment = engine_comment(reply.stats.score, reply.stats.depth);
+            self.play_turn(best_move, Some(comment));
+        }
+
+        self.turn_state = TurnStates::PieceSelection;
+        self.source_square = None;
+        self.legal_moves_from_source = Vec::new();
+    }
+
+    // a move made over the board, as opposed to one replayed by undo or redo: the mover's clock is pressed
+    fn play_turn(&mut self, mv: ChessMove, comment: Option<String>) {
+        let mover = self.position.board.side_to_move();
+        self.play_move(mv, comment);
+
+        if let Some(clocks) = &mut self.clocks {
+            clocks.press(mover);
+        }
+    }
+
+    // applies a move to the game, keeping the captured pieces, the move counters and the record up to date
+    fn play_move(&mut self, mv: ChessMove, comment: Option<String>) {
+        if let Some(captured) = captured_piece(&self.position.board, mv) {
+            match self.position.board.side_to_move() {
+                Color::White => self.black_slain_pieces.push(captured),
+                Color::Black => self.white_slain_pieces.push(captured),
             }
-    
-            self.turn_state = TurnStates::PieceSelection;
-            self.source_square = None;
-            self.legal_moves_from_source = Vec::new();
+        }
 
-        } else if self.board.status() == BoardStatus::Checkmate {
+        self.game_record.push_move(&self.position.board, mv, comment);
+        self.position.make_move(mv);
+        self.position_history.push(self.position.board.get_hash());
+    }
+
+    // takes back the last human move together with the engine's reply, cancelling a search in progress
+    pub fn undo(&mut self) {
+        if self.game_record.moves.is_empty() {
+            return;
+        }
+
+        let mut moves = std::mem::take(&mut self.game_record.moves);
+        let mut side_to_move = self.position.board.side_to_move();
+
+        while let Some(undone) = moves.pop() {
+            self.undone_moves.push(undone);
+            side_to_move = !side_to_move;
+
+            if side_to_move == self.human_color {
+                break;
+            }
+        }
+
+        self.restore(moves);
+    }
+
+    // plays the undone moves again up to the next human turn, without searching
+    pub fn redo(&mut self) {
+        if self.undone_moves.is_empty() {
+            return;
+        }
+
+        let mut moves = std::mem::take(&mut self.game_record.moves);
+        let mut side_to_move = self.position.board.side_to_move();
+
+        while let Some(redone) = self.undone_moves.pop() {
+            moves.push(redone);
+            side_to_move = !side_to_move;
+
+            if side_to_move == self.human_color {
+                break;
+            }
+        }
+
+        self.restore(moves);
+    }
+
+    // puts the game back to its start and plays `moves` from there, so the board, the captured
+    // pieces and the record cannot drift apart
+    fn restore(&mut self, moves: Vec<PgnMove>) {
+        self.reset_info();
+        self.position = self.game_record.start;
+        self.position_history = vec![self.position.board.get_hash()];
+        self.game_is_over = false;
+        self.winner = None;
+
+        let first_mover = self.position.board.side_to_move();
+        let first_mover_moves = moves.len().div_ceil(2) as u32;
+        let second_mover_moves = (moves.len() / 2) as u32;
+
+        for pgn_move in moves {
+            self.play_move(pgn_move.mv, pgn_move.comment);
+        }
+
+        // the clocks follow the moves left on the board, but the time already spent stays spent
+        if let Some(clocks) = &mut self.clocks {
+            clocks.set_moves_made(first_mover, first_mover_moves);
+            clocks.set_moves_made(!first_mover, second_mover_moves);
+            clocks.start(self.position.board.side_to_move());
+        }
+
+        self.turn_state = if self.position.board.side_to_move() == self.human_color {
+            TurnStates::PieceSelection
+        } else {
+            TurnStates::OpponentMoves
+        };
+    }
+
+    // starts over from `position` with the chosen side; the engine opens if it is its turn
+    pub fn start_game(&mut self, position: FenPosition) {
+        self.reset_info();
+        self.human_color = match self.side_choice {
+            SideChoice::White => Color::White,
+            SideChoice::Black => Color::Black,
+            SideChoice::Random => random_color(),
+        };
+        self.position = position;
+        self.position_history = vec![position.board.get_hash()];
+        self.game_record = PgnGame::new(position);
+        self.undone_moves = Vec::new();
+        self.pgn_status = None;
+        // a fresh engine, since the old one may still be locked by a cancelled search
+        self.engine = Arc::new(Mutex::new(Engine::new(self.hash_mb)));
+        self.game_is_over = false;
+        self.winner = None;
+        self.clocks = TIME_CONTROLS[self.time_control].1.map(Clocks::new);
+        if let Some(clocks) = &mut self.clocks {
+            clocks.start(position.board.side_to_move());
+        }
+        self.turn_state = if position.board.side_to_move() == self.human_color {
+            TurnStates::PieceSelection
+        } else {
+            TurnStates::OpponentMoves
+        };
+    }
+
+    pub fn load_fen(&mut self) {
+        match parse_fen(&self.fen_input) {
+            Ok(position) => {
+                self.fen_error = None;
+                self.start_game(position);
+            },
+            Err(error) => self.fen_error = Some(error.to_string()),
+        }
+    }
+
+    // writes the game so far to the PGN path, or, with none entered, into the working directory
+    // in a file named after the current time
+    pub fn save_pgn(&mut self) {
+        let result = match (self.game_is_over, self.winner) {
+            (true, Some(Color::White)) => PgnResult::WhiteWins,
+            (true, Some(Color::Black)) => PgnResult::BlackWins,
+            (true, None) => PgnResult::Draw,
+            (false, _) => PgnResult::Unfinished,
+        };
+
+        self.game_record.set_result(result);
+        self.game_record.set_tag("Event", "Casual game");
+        self.game_record.set_tag("Site", "rust-chess-engine");
+        self.game_record.set_tag("Round", "-");
+        let (white, black) = match self.human_color {
+            Color::White => ("Human", "rust-chess-engine"),
+            Color::Black => ("rust-chess-engine", "Human"),
+        };
+        self.game_record.set_tag("White", white);
+        self.game_record.set_tag("Black", black);
+
+        // the engine settings as they are when saving; with a clock the engine budgets its own
+        // time, so neither the depth nor the move time had a say
+        self.game_record.remove_tag("EngineDepth");
+        self.game_record.remove_tag("EngineMoveTime");
+        match (&self.clocks, self.search_mode) {
+            (Some(_), _) => (),
+            (None, SearchMode::FixedDepth) => self.game_record.set_tag("EngineDepth", &self.depth.to_string()),
+            (None, SearchMode::FixedTime) => self.game_record.set_tag("EngineMoveTime", &format!("{}ms", self.move_time_ms)),
+        }
+        self.game_record.set_tag("EnginePruning", &self.pruning.to_string());
+        self.game_record.set_tag("EngineMoveOrdering", &self.move_ordering.to_string());
+        self.game_record.set_tag("EngineHash", &format!("{}MB", self.hash_mb));
+        if let Some(clocks) = &self.clocks {
+            self.game_record.set_tag("TimeControl", &clocks.control.pgn_tag());
+        }
+
+        let path = match self.pgn_path.trim() {
+            "" => {
+                let seconds = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs());
+                format!("game-{}.pgn", seconds)
+            },
+            path => path.to_string(),
+        };
+
+        self.pgn_status = Some(match std::fs::write(&path, self.game_record.to_pgn()) {
+            Ok(()) => format!("saved to {}", path),
+            Err(error) => format!("could not save: {}", error),
+        });
+    }
+
+    // makes the search thread give up and reply with the best move it has so far
+    pub fn move_now(&self) {
+        self.stop_search.store(true, Ordering::Relaxed);
+    }
+
+    // ends the game if it is over on the board: checkmate, or a draw by any of the rules
+    fn game_continues(&mut self) -> bool {
+        if self.position.board.status() == BoardStatus::Checkmate {
             self.checkmate();
+            false
+        } else if let Some(reason) = draw_reason(&self.position, &self.position_history) {
+            self.turn_state = TurnStates::Draw(reason);
+            if let Some(clocks) = &mut self.clocks {
+                clocks.stop();
+            }
+            false
         } else {
-            self.turn_state = TurnStates::Stalemate;
+            true
         }
-     
     }
 
     fn checkmate(&mut self) {
         self.turn_state = TurnStates::Checkmate;
 
-        let checkmated_player = self.board.side_to_move();
+        let checkmated_player = self.position.board.side_to_move();
         self.winner = Some(
             if checkmated_player == Color::White {
                 Color::Black
@@ -170,13 +492,30 @@ impl ChessApp {
     }
 
     pub fn reset_info(&mut self) {
-        self.time_elapsed = Duration::ZERO;
-        self.count = 0;
+        self.stats = SearchStats::default();
+        self.principal_variation = Vec::new();
         self.source_square = None;
         self.legal_moves_from_source = Vec::new();
+        self.pending_promotion = None;
         self.white_slain_pieces = Vec::new();
         self.black_slain_pieces = Vec::new();
 
+        if let Some(clocks) = &mut self.clocks {
+            clocks.stop();
+        }
+
+        // an unfinished search is cancelled and its reply thrown away
+        self.stop_search.store(true, Ordering::Relaxed);
+        self.opponent_reply = None;
+    }
+}
+
+// std has no random numbers, but every RandomState is seeded differently
+fn random_color() -> Color {
+    if RandomState::new().build_hasher().finish() & 1 == 0 {
+        Color::White
+    } else {
+        Color::Black
     }
 }
 
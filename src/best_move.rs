@@ -1,223 +1,526 @@
 use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
-use crate::piece_square_tables::{BISHOP_PST, EG_KING_PST, KNIGHT_PST, MG_KING_PST, PAWN_PST, QUEEN_PST, ROOK_PST};
+use crate::evaluation::{evaluate_board, evaluate_with_pawn_table, piece_value};
+use crate::fen::FenPosition;
+use crate::move_ordering::{captured_piece, is_noisy, mvv_lva, MoveOrdering};
+use crate::pawn_structure::PawnTable;
+use crate::transposition_table::{Bound, TranspositionTable, TtEntry};
+
+// algoritmos que podem ser ligados e desligados para comparar o numero de nos
+#[derive(Clone, Copy)]
+pub struct SearchOptions {
+    pub pruning: bool,
+    pub move_ordering: bool,
+    // quanto o motor despreza um empate, em centipeoes: positivo evita repeticoes, negativo as procura
+    pub contempt: i32,
+}
+
+// limites da busca: ela para no que acontecer primeiro
+#[derive(Clone, Copy)]
+pub struct SearchLimits {
+    pub depth: u32,
+    pub move_time: Option<Duration>,
+    pub nodes: Option<i64>,
+}
+
+impl SearchLimits {
+    pub fn fixed_depth(depth: u32) -> Self {
+        Self { depth, move_time: None, nodes: None }
+    }
+
+    pub fn fixed_time(move_time: Duration) -> Self {
+        Self { depth: MAX_DEPTH, move_time: Some(move_time), nodes: None }
+    }
+
+    // gerenciamento de tempo: uma fracao do relogio restante mais parte do incremento,
+    // sem nunca passar da metade do que sobrou
+    pub fn from_clock(time_left: Duration, increment: Duration, moves_to_go: Option<u32>) -> Self {
+        let moves_to_go = moves_to_go.unwrap_or(30).max(1);
+        let budget = time_left / moves_to_go + increment / 2;
+        // margem para a comunicacao com a interface
+        let overhead = Duration::from_millis(30);
+
+        Self::fixed_time(budget.min(time_left / 2).saturating_sub(overhead).max(Duration::from_millis(1)))
+    }
+}
+
+// resultado da busca; assim como em evaluate_board, a pontuacao eh do ponto de vista das brancas
+#[derive(Clone, Default)]
+pub struct SearchResult {
+    pub score: i32,
+    pub best_move: Option<ChessMove>,
+    pub pv: Vec<ChessMove>,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct SearchStats {
+    pub count: i64,
+    pub qnodes: i64,
+    pub time_elapsed: Duration,
+    pub depth: u32,
+    pub score: i32,
+    pub tt_hits: u64,
+    pub tt_overwrites: u64,
+}
+
+pub const MAX_DEPTH: u32 = 64;
+
+// pontuacao de um xeque-mate na raiz; mates mais distantes valem um pouco menos
+pub const MATE_SCORE: i32 = 1_000_000;
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1000;
+
+// maior que qualquer pontuacao e, ao contrario de i32::MIN, pode ser negado
+const INFINITY: i32 = MATE_SCORE + 1;
+
+// decide quando a busca deve ser interrompida: pelo botao, pelo tempo ou pelo numero de nos
+struct SearchControl<'a> {
+    stop: &'a AtomicBool,
+    deadline: Option<Instant>,
+    max_nodes: Option<i64>,
+    count: i64,
+    qnodes: i64,
+    tt: &'a mut TranspositionTable,
+    // recriada a cada busca, ao contrario da tabela de transposicao
+    pawn_table: PawnTable,
+    pruning: bool,
+    move_ordering: bool,
+    ordering: MoveOrdering,
+    // hashes das posicoes da partida e do caminho da busca ate o no atual, sem ele
+    history: Vec<u64>,
+    // lances desde a ultima captura ou movimento de peao, no no atual
+    halfmove_clock: u32,
+    contempt: i32,
+}
+
+impl SearchControl<'_> {
+    fn should_stop(&self) -> bool {
+        let total = self.count + self.qnodes;
+        let out_of_nodes = self.max_nodes.is_some_and(|nodes| total >= nodes);
+        // consultar o relogio a cada no seria caro demais
+        let out_of_time = total % 1024 == 0 && self.deadline.is_some_and(|deadline| Instant::now() >= deadline);
+
+        if out_of_nodes || out_of_time {
+            self.stop.store(true, Ordering::Relaxed);
+        }
+
+        self.stopped()
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
 
+    // so as posicoes desde o ultimo lance irreversivel podem se repetir
+    fn is_repetition(&self, key: u64) -> bool {
+        self.history.iter().rev().take(self.halfmove_clock as usize).any(|earlier| *earlier == key)
+    }
+
+    // o empate vale -contempt para o lado que comecou a busca e +contempt para o outro
+    fn draw_score(&self, ply: u32) -> i32 {
+        if ply.is_multiple_of(2) { -self.contempt } else { self.contempt }
+    }
+
+    fn update_stats(&self, stats: &mut SearchStats, result: &SearchResult, start: Instant) {
+        stats.count = self.count;
+        stats.qnodes = self.qnodes;
+        stats.tt_hits = self.tt.hits;
+        stats.tt_overwrites = self.tt.overwrites;
+        stats.score = result.score;
+        stats.time_elapsed = start.elapsed();
+    }
+}
+
+// aprofundamento iterativo: busca com profundidade 1, 2, 3... ate esgotar algum limite.
+// `history` sao os hashes das posicoes anteriores da partida, para reconhecer repeticoes.
+// `report` eh chamado ao fim de cada iteracao completa (usado pelas linhas "info" do uci)
+#[allow(clippy::too_many_arguments)]
 pub fn best_move(
-    has_pruning: &bool,
-    board: &Board,
-    depth: u32,
-    maximizing: bool,
-    count: &mut i64,
-    time_elapsed: &mut Duration,
-) -> (i32, Option<ChessMove>) {
-    let result: (i32, Option<ChessMove>);
+    options: &SearchOptions,
+    position: &FenPosition,
+    history: &[u64],
+    limits: &SearchLimits,
+    stats: &mut SearchStats,
+    stop: &AtomicBool,
+    tt: &mut TranspositionTable,
+    report: &mut dyn FnMut(&SearchResult, &SearchStats),
+) -> SearchResult {
     let now = Instant::now();
+    let board = &position.board;
+
+    tt.reset_stats();
+
+    let mut control = SearchControl {
+        stop,
+        deadline: limits.move_time.map(|move_time| now + move_time),
+        max_nodes: limits.nodes,
+        count: 0,
+        qnodes: 0,
+        tt,
+        pawn_table: PawnTable::new(),
+        pruning: options.pruning,
+        move_ordering: options.move_ordering,
+        ordering: MoveOrdering::new(),
+        history: history.to_vec(),
+        halfmove_clock: position.halfmove_clock,
+        contempt: options.contempt,
+    };
 
-    *count = 0;
+    *stats = SearchStats::default();
 
-    if *has_pruning {
-        result = minimax_alpha_beta(
-            board,
-            depth,
-            i32::MIN,
-            i32::MAX,
-            maximizing,
-            count);
-    } else {
-        result = minimax(
-            board,
-            depth,
-            maximizing,
-            count,
-        );
+    let mut result = SearchResult {
+        score: evaluate_board(board),
+        best_move: None,
+        pv: Vec::new(),
     };
 
-    *time_elapsed = now.elapsed();
+    for depth in 1..=limits.depth.max(1) {
+        let mut pv = Vec::new();
+        let score = negamax(board, depth, 0, -INFINITY, INFINITY, &mut control, &mut pv);
+
+        let iteration = SearchResult {
+            score: white_relative(board, score),
+            best_move: pv.first().copied(),
+            pv,
+        };
+
+        // uma iteracao interrompida pode nao ter visto o melhor movimento,
+        // entao so eh usada se nenhuma outra terminou
+        if control.stopped() {
+            if result.best_move.is_none() {
+                result = iteration;
+            }
+            break;
+        }
 
-    result
-}
+        result = iteration;
+        stats.depth = depth;
+        control.update_stats(stats, &result, now);
+        report(&result, stats);
 
-pub fn evaluate_board(board: &Board) -> i32 {
-    let mut result = 0;
+        // um mate encontrado dentro da profundidade buscada nao muda mais
+        if MATE_SCORE - result.score.abs() <= depth as i32 {
+            break;
+        }
 
-    for square in *board.color_combined(Color::White) {
-        let piece = board.piece_on(square).unwrap();
-        result += piece_value(board.piece_on(square).unwrap());
-        result += piece_square_value(board, piece, square, Color::White);
+        // a proxima iteracao custa bem mais que todas as anteriores juntas,
+        // entao nem comeca se metade do tempo ja foi gasto
+        if let Some(move_time) = limits.move_time {
+            if now.elapsed() * 2 >= move_time {
+                break;
+            }
+        }
     }
 
-    for square in *board.color_combined(Color::Black) {
-        let piece = board.piece_on(square).unwrap();
-        result -= piece_value(board.piece_on(square).unwrap());
-        result -= piece_square_value(board, piece, square, Color::Black);
+    // se a busca foi interrompida antes de terminar o primeiro movimento,
+    // ainda eh preciso devolver algum movimento legal
+    if result.best_move.is_none() && control.stopped() {
+        result.score = evaluate_board(board);
+        result.best_move = MoveGen::new_legal(board).next();
+        result.pv = result.best_move.into_iter().collect();
     }
 
+    control.update_stats(stats, &result, now);
+
     result
 }
 
-fn piece_value(piece: Piece) -> i32 {
-    match piece {
-        Piece::Pawn => 100,
-        Piece::Knight => 320,
-        Piece::Bishop => 330,
-        Piece::Rook => 500,
-        Piece::Queen => 900,
-        Piece::King => 20000,
-    }
-}
-
-fn piece_square_value(board: &Board, piece: Piece, square: chess::Square, color: Color) -> i32 {
-    let index = square.to_index();
-    match piece {
-        Piece::Pawn => if color == Color::White { PAWN_PST[index] } else { PAWN_PST[mirror_index(index)] },
-        Piece::Knight => if color == Color::White { KNIGHT_PST[index] } else { KNIGHT_PST[mirror_index(index)] },
-        Piece::Bishop =>  if color == Color::White { BISHOP_PST[index] } else { BISHOP_PST[mirror_index(index)] },
-        Piece::Rook =>  if color == Color::White { ROOK_PST[index] } else { ROOK_PST[mirror_index(index)] },
-        Piece::Queen =>  if color == Color::White { QUEEN_PST[index] } else { QUEEN_PST[mirror_index(index)] },
-        Piece::King =>  {
-
-            if !check_special_endgame(board) {
-                if color == Color::White { 
-                    MG_KING_PST[index] 
-                } else { 
-                    MG_KING_PST[mirror_index(index)] 
-                }
-            } else {
-                if color == Color::White { 
-                    EG_KING_PST[index] 
-                } else { 
-                    EG_KING_PST[mirror_index(index)] 
-                }
-            }      
-        },    
+// xeque-mate vale MATE_SCORE menos a distancia ate a raiz, para preferir o mate mais
+// rapido (e adiar o proprio mate o maximo possivel); afogamento eh empate.
+// como no negamax, a pontuacao eh do ponto de vista de quem joga
+fn terminal_score(board: &Board, ply: u32) -> i32 {
+    match board.status() {
+        BoardStatus::Checkmate => -(MATE_SCORE - ply as i32),
+        _ => 0,
     }
 }
 
-fn check_special_endgame(board: &Board) -> bool {
-    
-    let white_queens = (board.pieces(Piece::Queen) & board.color_combined(Color::White)).popcnt();
-    let black_queens = (board.pieces(Piece::Queen) & board.color_combined(Color::Black)).popcnt();
-    
-    let white_count = count_pieces(board, Color::White);
-    let black_count = count_pieces(board, Color::Black);
+// um corte pela tabela nao percorre a linha principal, entao o resto dela eh refeito com os
+// melhores movimentos guardados, ate `depth` lances; para num movimento ilegal (colisao de hash)
+// ou quando uma posicao se repete
+fn pv_from_tt(board: &Board, first: ChessMove, depth: u32, tt: &TranspositionTable) -> Vec<ChessMove> {
+    let mut pv = vec![first];
+    let mut board = board.make_move_new(first);
+    let mut seen = vec![board.get_hash()];
+
+    while (pv.len() as u32) < depth {
+        let Some(next) = tt.best_move(board.get_hash()) else {
+            break;
+        };
+        if !board.legal(next) {
+            break;
+        }
 
-    let white_condition = (white_queens == 0) || (white_queens == 1 && white_count <= 1);
-    let black_condition = (black_queens == 0) || (black_queens == 1 && black_count <= 1);
+        board = board.make_move_new(next);
+        if seen.contains(&board.get_hash()) {
+            break;
+        }
+        seen.push(board.get_hash());
+        pv.push(next);
+    }
 
-    white_condition && black_condition
+    pv
 }
 
-fn count_pieces(board: &Board, color: Color) -> u32 {
-    let bishops = (board.pieces(Piece::Bishop) & board.color_combined(color)).popcnt();
-    let knights = (board.pieces(Piece::Knight) & board.color_combined(color)).popcnt();
-    let rooks = (board.pieces(Piece::Rook) & board.color_combined(color)).popcnt();
-    let queens = (board.pieces(Piece::Queen) & board.color_combined(color)).popcnt();
-    
-    bishops + knights + rooks + queens
+// evaluate_board eh do ponto de vista das brancas; o negamax precisa do ponto de vista de quem joga
+fn relative_evaluation(board: &Board, pawn_table: &mut PawnTable) -> i32 {
+    white_relative(board, evaluate_with_pawn_table(board, pawn_table))
 }
 
-fn mirror_index(index: usize) -> usize {
-    63 - index
+// a mesma troca de sinal serve nos dois sentidos
+fn white_relative(board: &Board, score: i32) -> i32 {
+    if board.side_to_move() == Color::White { score } else { -score }
+}
+
+// numero de lances ate o mate, negativo se quem sofre o mate eh o lado da pontuacao
+pub fn mate_distance(score: i32) -> Option<i32> {
+    if score.abs() >= MATE_THRESHOLD {
+        let moves = (MATE_SCORE - score.abs() + 1) / 2;
+        Some(if score > 0 { moves } else { -moves })
+    } else {
+        None
+    }
+}
+
+// texto da pontuacao para a interface: "mate in N" ou a avaliacao em peoes
+pub fn format_score(score: i32) -> String {
+    match mate_distance(score) {
+        Some(moves) => {
+            let winner = if moves > 0 { "White" } else { "Black" };
+            format!("{} mates in {}", winner, moves.abs())
+        },
+        None => format!("{:+.2}", score as f32 / 100.0),
+    }
 }
 
-fn minimax(
+// negamax: max(a, b) = -min(-a, -b), entao um unico codigo serve para os dois lados,
+// bastando trocar o sinal da pontuacao (e da janela alpha-beta) a cada nivel.
+// sem poda, ele eh o minimax puro e serve de referencia para comparar o numero de nos.
+// a variante principal (a sequencia esperada de movimentos) eh devolvida em `pv`
+fn negamax(
     board: &Board,
     depth: u32,
-    maximizing: bool,
-    count: &mut i64,
-) -> (i32, Option<ChessMove>) {
-    *count += 1;
+    ply: u32,
+    alpha: i32,
+    beta: i32,
+    control: &mut SearchControl,
+    pv: &mut Vec<ChessMove>,
+) -> i32 {
+    // as folhas sao a raiz da busca quiescente e contam como nos dela
+    if depth == 0 {
+        control.qnodes += 1;
+    } else {
+        control.count += 1;
+    }
+
+    if control.should_stop() {
+        return 0;
+    }
 
     // ponto de parada da recursao:
-    // eh necessario checar se chegou a profundidade estipulada
-    // ou se board apresenta um jogo finalizado
-    if depth == 0 || board.status() != BoardStatus::Ongoing {
-        return (evaluate_board(board), None);
+    // eh necessario checar se board apresenta um jogo finalizado
+    // ou se chegou a profundidade estipulada
+    if board.status() != BoardStatus::Ongoing {
+        return terminal_score(board, ply);
+    }
+
+    let key = board.get_hash();
+
+    // repeticao ou regra dos 50 lances (o mate ja foi tratado acima). uma unica repeticao
+    // ja basta: se repetir uma vez era bom, repetir de novo tambem seria
+    if ply > 0 && (control.halfmove_clock >= 100 || control.is_repetition(key)) {
+        return control.draw_score(ply);
+    }
+
+    // nas folhas, as capturas pendentes ainda sao resolvidas pela busca quiescente.
+    // ela roda com ou sem poda, para que as duas buscas cheguem ao mesmo resultado
+    // (sem poda, a janela continua sendo a inteira)
+    if depth == 0 {
+        return quiescence(board, ply, alpha, beta, control);
+    }
+
+    let tt_entry = if control.pruning { control.tt.probe(key) } else { None };
+
+    // uma busca anterior pelo menos tao profunda ja resolveu essa posicao
+    // (na raiz nao, para sempre sobrar um movimento)
+    if let Some(entry) = tt_entry {
+        if ply > 0 && entry.depth >= depth {
+            let score = score_from_tt(entry.score, ply);
+            let cutoff = match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => score >= beta,
+                Bound::Upper => score <= alpha,
+            };
+
+            if cutoff {
+                pv.clear();
+                if let Some(best_move) = entry.best_move {
+                    *pv = pv_from_tt(board, best_move, depth, control.tt);
+                }
+                return score;
+            }
+        }
     }
 
+    // representa o maior resultado garantido para quem joga naquele caminho;
+    // beta eh o maximo que o adversario ainda permite
+    let mut alpha = alpha;
+    let original_alpha = alpha;
     let mut best_move = None;
-    let mut best_score = if maximizing { i32::MIN } else { i32::MAX };
+    let mut best_score = -INFINITY;
+    let mut child_pv = Vec::new();
+
+    // os movimentos mais promissores sao testados primeiro, o que gera mais cortes
+    let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+    if control.pruning && control.move_ordering {
+        let tt_move = tt_entry.and_then(|entry| entry.best_move);
+        control.ordering.order_moves(board, &mut moves, tt_move, ply);
+    }
 
     // itera por todos os movimentos legais no estado do tabuleiro atual
-    for mv in MoveGen::new_legal(board) {
+    for mv in moves {
         let new_board = board.make_move_new(mv); //tabuleiro que representa um possivel movimento
-        let (score, _) = minimax(&new_board, depth - 1, !maximizing, count);
 
-        //se for a vez das brancas:
-        if maximizing {
-            if score > best_score {
-                best_score = score;
-                best_move = Some(mv);
-            }
-        } else {
-            //se for a vez das pretas:
-            if score < best_score {
-                best_score = score;
-                best_move = Some(mv);
+        let halfmove_clock = control.halfmove_clock;
+        let irreversible = board.piece_on(mv.get_source()) == Some(Piece::Pawn) || captured_piece(board, mv).is_some();
+        control.halfmove_clock = if irreversible { 0 } else { halfmove_clock + 1 };
+        control.history.push(key);
+
+        child_pv.clear();
+        let score = -negamax(&new_board, depth - 1, ply + 1, -beta, -alpha, control, &mut child_pv);
+
+        control.history.pop();
+        control.halfmove_clock = halfmove_clock;
+
+        // o resultado de uma busca interrompida nao eh confiavel, entao fica o melhor ate aqui
+        if control.stopped() {
+            break;
+        }
+
+        if score > best_score {
+            best_score = score;
+            best_move = Some(mv);
+
+            pv.clear();
+            pv.push(mv);
+            pv.extend_from_slice(&child_pv);
+        }
+
+        if control.pruning {
+            alpha = alpha.max(score);
+
+            //se alpha alcancar beta, o adversario nunca deixaria o jogo chegar aqui, ent nao precisa continuar o for loop
+            if alpha >= beta {
+                control.ordering.record_cutoff(board, mv, depth, ply);
+                break;
             }
         }
     }
 
-    (best_score, best_move)
+    if control.pruning && !control.stopped() {
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+
+        control.tt.store(TtEntry {
+            key,
+            depth,
+            bound,
+            score: score_to_tt(best_score, ply),
+            best_move,
+        });
+    }
+
+    best_score
 }
 
-fn minimax_alpha_beta(
+// margem de seguranca da poda delta: uma captura que nem com essa folga
+// alcanca alpha nao precisa ser testada
+const DELTA_MARGIN: i32 = 200;
+
+// busca quiescente: continua apenas com capturas e promocoes ate a posicao ficar
+// calma, para nao avaliar no meio de uma troca (efeito horizonte)
+fn quiescence(
     board: &Board,
-    depth: u32,
+    ply: u32,
     alpha: i32,
     beta: i32,
-    maximizing: bool,
-    count: &mut i64,
-) -> (i32, Option<ChessMove>) {
-    *count += 1;
-
-    // ponto de parada da recursao:
-    // eh necessario checar se chegou a profundidade estipulada
-    // ou se board apresenta um jogo finalizado
-    if depth == 0 || board.status() != BoardStatus::Ongoing {
-        return (evaluate_board(board), None);
+    control: &mut SearchControl,
+) -> i32 {
+    if control.should_stop() {
+        return 0;
     }
 
-    // representa o maior resultado encontrado naquele caminho
-    // o valor inicial eh o menor possivel, pois nenhum valor foi procurado ainda
-    let mut alpha = alpha;
-
-    // representa o menor resultado encontrado naquele caminho
-    // o valor inicial eh o maior possivel, pois nenhum valor foi procurado ainda
-    let mut beta = beta;
-    let mut best_move = None;
-    let mut best_score = if maximizing { i32::MIN } else { i32::MAX };
-
-    // itera por todos os movimentos legais no estado do tabuleiro atual
-    for mv in MoveGen::new_legal(board) {
-        let new_board = board.make_move_new(mv); //tabuleiro que representa um possivel movimento
-        let (score, _) = minimax_alpha_beta(&new_board, depth - 1, alpha, beta, !maximizing, count);
+    let legal_moves = MoveGen::new_legal(board);
+    if legal_moves.len() == 0 {
+        return terminal_score(board, ply);
+    }
 
-        //se for a vez das brancas:
-        if maximizing {
-            if score > best_score {
-                best_score = score;
-                best_move = Some(mv);
+    // stand pat: o lado a jogar pode simplesmente nao capturar nada
+    let stand_pat = relative_evaluation(board, &mut control.pawn_table);
+    if stand_pat >= beta {
+        return stand_pat;
+    }
+    let mut alpha = alpha.max(stand_pat);
+
+    let mut moves: Vec<ChessMove> = legal_moves.filter(|mv| is_noisy(board, *mv)).collect();
+    moves.sort_by_cached_key(|mv| match captured_piece(board, *mv) {
+        Some(victim) => -mvv_lva(victim, board.piece_on(mv.get_source()).unwrap()),
+        None => 0,
+    });
+
+    let mut best_score = stand_pat;
+
+    for mv in moves {
+        // poda delta
+        if mv.get_promotion().is_none() {
+            let gain = captured_piece(board, mv).map_or(0, piece_value) + DELTA_MARGIN;
+            if stand_pat + gain < alpha {
+                continue;
             }
+        }
 
-            //se o score for o maior encontrado ate agr:
-            alpha = alpha.max(score);
-        } else {
-            //se for a vez das pretas:
-            if score < best_score {
-                best_score = score;
-                best_move = Some(mv);
-            }
+        let new_board = board.make_move_new(mv);
+        control.qnodes += 1;
+        let score = -quiescence(&new_board, ply + 1, -beta, -alpha, control);
 
-            //se o score for o menor encontrado ate agora
-            beta = beta.min(score);
+        if control.stopped() {
+            break;
         }
 
-        //se beta for maior que alpha significa q um caminho mais favoravel ja foi garantido, ent nao precisa continuar o for loop
-        if beta <= alpha {
+        best_score = best_score.max(score);
+        alpha = alpha.max(score);
+
+        if alpha >= beta {
             break;
         }
     }
 
-    (best_score, best_move)
+    best_score
+}
+
+// pontuacoes de mate sao relativas a raiz, mas na tabela precisam ser relativas
+// a propria posicao, ja que ela pode ser alcancada em outra distancia da raiz
+fn score_to_tt(score: i32, ply: u32) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score + ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score - ply as i32
+    } else {
+        score
+    }
+}
+
+fn score_from_tt(score: i32, ply: u32) -> i32 {
+    if score >= MATE_THRESHOLD {
+        score - ply as i32
+    } else if score <= -MATE_THRESHOLD {
+        score + ply as i32
+    } else {
+        score
+    }
 }
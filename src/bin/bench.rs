@@ -0,0 +1,66 @@
+use std::str::FromStr;
+use std::sync::atomic::AtomicBool;
+
+use chess::Board;
+use thousands::Separable;
+use rust_chess_engine::{Engine, SearchLimits, SearchOptions};
+
+const BENCH_DEPTH: u32 = 5;
+
+// a fixed set of positions: opening, tactical middlegame, and endgames
+const BENCH_POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "8/5pk1/6p1/7p/P6P/6P1/r4PK1/1R6 w - - 0 40",
+];
+
+// `cargo run --release --bin bench`: searches every position with and without move ordering and
+// prints how many nodes the ordering saves
+fn main() {
+    let mut total_unordered = 0;
+    let mut total_ordered = 0;
+
+    println!("alpha-beta at depth {}, nodes without / with move ordering:", BENCH_DEPTH);
+
+    for fen in BENCH_POSITIONS {
+        let board = Board::from_str(fen).unwrap();
+        let unordered = search_nodes(&board, false);
+        let ordered = search_nodes(&board, true);
+
+        total_unordered += unordered;
+        total_ordered += ordered;
+
+        println!(
+            "{:>12} / {:>12}  {:>5.1}% saved  {}",
+            unordered.separate_with_commas(),
+            ordered.separate_with_commas(),
+            saved_percent(unordered, ordered),
+            fen
+        );
+    }
+
+    println!(
+        "{:>12} / {:>12}  {:>5.1}% saved  total",
+        total_unordered.separate_with_commas(),
+        total_ordered.separate_with_commas(),
+        saved_percent(total_unordered, total_ordered)
+    );
+}
+
+fn search_nodes(board: &Board, move_ordering: bool) -> i64 {
+    let mut engine = Engine::default();
+    engine.set_options(SearchOptions { pruning: true, move_ordering, contempt: 0 });
+    engine.set_position(*board);
+    engine.search(&SearchLimits::fixed_depth(BENCH_DEPTH), &AtomicBool::new(false));
+
+    // the quiescence nodes too, the way the uci info line counts them
+    let stats = engine.last_stats();
+    stats.count + stats.qnodes
+}
+
+fn saved_percent(unordered: i64, ordered: i64) -> f64 {
+    100.0 * (unordered - ordered) as f64 / unordered as f64
+}
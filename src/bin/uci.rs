@@ -0,0 +1,4 @@
+// the engine for chess guis (cutechess-cli, arena, ...), without the egui window
+fn main() {
+    rust_chess_engine::uci::run();
+}
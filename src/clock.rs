@@ -0,0 +1,210 @@
+use std::time::{Duration, Instant};
+
+use chess::Color;
+use eframe::egui::{self, Color32, FontId, Pos2, Rect, Vec2};
+use super::ChessApp;
+use crate::action_manager::TurnStates;
+
+#[derive(PartialEq, Clone, Copy)]
+pub struct TimeControl {
+    pub base: Duration,
+    pub increment: Duration,
+    // every this many moves, the base time is added to the clock again
+    pub moves_per_session: Option<u32>,
+}
+
+impl TimeControl {
+    const fn new(base_minutes: u64, increment_seconds: u64, moves_per_session: Option<u32>) -> Self {
+        Self {
+            base: Duration::from_secs(base_minutes * 60),
+            increment: Duration::from_secs(increment_seconds),
+            moves_per_session,
+        }
+    }
+
+    // the TimeControl tag of the pgn standard: "300+3", or "40/5400" for sessions
+    pub fn pgn_tag(&self) -> String {
+        let mut tag = self.base.as_secs().to_string();
+        if let Some(moves) = self.moves_per_session {
+            tag = format!("{}/{}", moves, tag);
+        }
+        if !self.increment.is_zero() {
+            tag += &format!("+{}", self.increment.as_secs());
+        }
+        tag
+    }
+}
+
+pub const TIME_CONTROLS: [(&str, Option<TimeControl>); 7] = [
+    ("untimed", None),
+    ("1+0", Some(TimeControl::new(1, 0, None))),
+    ("3+2", Some(TimeControl::new(3, 2, None))),
+    ("5+3", Some(TimeControl::new(5, 3, None))),
+    ("15+10", Some(TimeControl::new(15, 10, None))),
+    ("30+0", Some(TimeControl::new(30, 0, None))),
+    ("40 moves / 90 min", Some(TimeControl::new(90, 0, Some(40)))),
+];
+
+// both players' clocks; at most one of them runs at a time
+pub struct Clocks {
+    pub control: TimeControl,
+    remaining: [Duration; 2],
+    moves_made: [u32; 2],
+    running: Option<(Color, Instant)>,
+}
+
+impl Clocks {
+    pub fn new(control: TimeControl) -> Self {
+        Self {
+            control,
+            remaining: [control.base; 2],
+            moves_made: [0; 2],
+            running: None,
+        }
+    }
+
+    pub fn time_left(&self, color: Color) -> Duration {
+        let remaining = self.remaining[color.to_index()];
+        match self.running {
+            Some((running, since)) if running == color => remaining.saturating_sub(since.elapsed()),
+            _ => remaining,
+        }
+    }
+
+    // moves left before the next session's time comes in
+    pub fn moves_to_go(&self, color: Color) -> Option<u32> {
+        self.control
+            .moves_per_session
+            .map(|moves| moves - self.moves_made[color.to_index()] % moves)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn start(&mut self, color: Color) {
+        self.stop();
+        self.running = Some((color, Instant::now()));
+    }
+
+    // keeps the time used so far and leaves both clocks still
+    pub fn stop(&mut self) {
+        if let Some((color, _)) = self.running {
+            self.remaining[color.to_index()] = self.time_left(color);
+            self.running = None;
+        }
+    }
+
+    // `color` has moved: its clock gets the increment (and a new session, if one is due)
+    // and the opponent's clock starts
+    pub fn press(&mut self, color: Color) {
+        self.stop();
+        self.set_moves_made(color, self.moves_made[color.to_index()] + 1);
+        self.start(!color);
+    }
+
+    // undo and redo move the game back and forth: the increments and sessions of the moves in
+    // between are taken back or handed out again, while the time spent thinking stays spent
+    pub fn set_moves_made(&mut self, color: Color, moves_made: u32) {
+        let index = color.to_index();
+
+        while self.moves_made[index] > moves_made {
+            if self.session_due(index) {
+                self.remaining[index] = self.remaining[index].saturating_sub(self.control.base);
+            }
+            self.remaining[index] = self.remaining[index].saturating_sub(self.control.increment);
+            self.moves_made[index] -= 1;
+        }
+
+        while self.moves_made[index] < moves_made {
+            self.moves_made[index] += 1;
+            self.remaining[index] += self.control.increment;
+            if self.session_due(index) {
+                self.remaining[index] += self.control.base;
+            }
+        }
+    }
+
+    // whether the last move made by the player at `index` brought in a new session
+    fn session_due(&self, index: usize) -> bool {
+        match self.control.moves_per_session {
+            Some(moves) => self.moves_made[index] > 0 && self.moves_made[index].is_multiple_of(moves),
+            None => false,
+        }
+    }
+
+    pub fn flagged(&self) -> Option<Color> {
+        [Color::White, Color::Black].into_iter().find(|color| self.time_left(*color).is_zero())
+    }
+}
+
+impl ChessApp {
+    // ends the game when someone runs out of time
+    pub fn check_flag(&mut self) {
+        let flagged = match &self.clocks {
+            Some(clocks) => clocks.flagged(),
+            None => None,
+        };
+
+        if let Some(loser) = flagged {
+            self.reset_info();
+            self.winner = Some(!loser);
+            self.turn_state = TurnStates::OutOfTime(loser);
+        }
+    }
+
+    // one clock beside each player's edge of the board, right of the evaluation bar
+    pub fn display_clocks(&self, ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8]) {
+        let Some(clocks) = &self.clocks else {
+            return;
+        };
+
+        let size = Vec2::new(90.0, 36.0);
+        let left = tiles[0][7].max.x + 45.0;
+        let (top_color, bottom_color) = if self.white_at_bottom() {
+            (Color::Black, Color::White)
+        } else {
+            (Color::White, Color::Black)
+        };
+
+        for (color, top) in [(top_color, tiles[0][7].min.y), (bottom_color, tiles[7][7].max.y - size.y)] {
+            let rect = Rect::from_min_size(Pos2::new(left, top), size);
+            let time_left = clocks.time_left(color);
+            let active = clocks.is_running() && self.position.board.side_to_move() == color;
+
+            let (fill, text_color) = match color {
+                Color::White => (Color32::from_rgb(230, 230, 230), Color32::BLACK),
+                Color::Black => (Color32::from_rgb(40, 40, 40), Color32::WHITE),
+            };
+            let stroke = if active {
+                egui::Stroke::new(3.0, Color32::from_rgb(100, 200, 100))
+            } else {
+                egui::Stroke::NONE
+            };
+
+            ui.painter().rect(rect, 4.0, fill, stroke);
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                format_clock(time_left),
+                FontId::monospace(22.0),
+                text_color,
+            );
+        }
+
+        // the running clock has to be redrawn even when nothing else happens
+        if clocks.is_running() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+    }
+}
+
+// "m:ss", with tenths under ten seconds
+fn format_clock(time: Duration) -> String {
+    let seconds = time.as_secs();
+    if seconds < 10 {
+        format!("0:0{}.{}", seconds, time.subsec_millis() / 100)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+
+use chess::{BoardStatus, Color, Piece};
+use crate::fen::FenPosition;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DrawReason {
+    Stalemate,
+    Repetition,
+    FiftyMoves,
+    InsufficientMaterial,
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DrawReason::Stalemate => write!(f, "stalemate"),
+            DrawReason::Repetition => write!(f, "threefold repetition"),
+            DrawReason::FiftyMoves => write!(f, "the fifty-move rule"),
+            DrawReason::InsufficientMaterial => write!(f, "insufficient material"),
+        }
+    }
+}
+
+// why the game is drawn, if it is. `history` holds the hash of every position of the game,
+// the current one included; checkmate is the caller's business and wins over all of these
+pub fn draw_reason(position: &FenPosition, history: &[u64]) -> Option<DrawReason> {
+    let board = &position.board;
+
+    if board.status() == BoardStatus::Stalemate {
+        Some(DrawReason::Stalemate)
+    } else if insufficient_material(board) {
+        Some(DrawReason::InsufficientMaterial)
+    } else if position.halfmove_clock >= 100 {
+        Some(DrawReason::FiftyMoves)
+    } else if history.iter().filter(|hash| **hash == board.get_hash()).count() >= 3 {
+        Some(DrawReason::Repetition)
+    } else {
+        None
+    }
+}
+
+// dead positions nobody can win: king against king, alone or with a single minor piece
+pub fn insufficient_material(board: &chess::Board) -> bool {
+    let heavy_or_pawns = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+    if heavy_or_pawns.popcnt() > 0 {
+        return false;
+    }
+
+    let minors = |color: Color| {
+        ((board.pieces(Piece::Knight) | board.pieces(Piece::Bishop)) & board.color_combined(color)).popcnt()
+    };
+
+    minors(Color::White) + minors(Color::Black) <= 1
+}
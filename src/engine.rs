@@ -0,0 +1,141 @@
+use std::sync::atomic::AtomicBool;
+
+use chess::{Board, ChessMove};
+use crate::fen::{parse_fen, to_fen, FenError, FenPosition};
+use crate::best_move::{best_move, SearchLimits, SearchOptions, SearchResult, SearchStats};
+use crate::evaluation::{evaluate_board, evaluation_trace, EvalTrace};
+use crate::transposition_table::TranspositionTable;
+
+pub const DEFAULT_HASH_MB: usize = 16;
+
+// the engine as seen by its front-ends (the gui, the uci loop, tools and tests):
+// a position, the search settings and the transposition table kept between searches
+pub struct Engine {
+    position: FenPosition,
+    // hashes of the positions that came before `position` in the game, oldest first
+    history: Vec<u64>,
+    options: SearchOptions,
+    transposition_table: TranspositionTable,
+    stats: SearchStats,
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new(DEFAULT_HASH_MB)
+    }
+}
+
+impl Engine {
+    pub fn new(hash_mb: usize) -> Self {
+        Self {
+            position: FenPosition::default(),
+            history: Vec::new(),
+            options: SearchOptions { pruning: true, move_ordering: true, contempt: 0 },
+            transposition_table: TranspositionTable::new(hash_mb),
+            stats: SearchStats::default(),
+        }
+    }
+
+    pub fn position(&self) -> &Board {
+        &self.position.board
+    }
+
+    // the move counters and the game history start over, use `set_fen` or `set_game` to keep them
+    pub fn set_position(&mut self, board: Board) {
+        self.position = FenPosition { board, ..FenPosition::default() };
+        self.history.clear();
+    }
+
+    pub fn set_fen(&mut self, fen: &str) -> Result<(), FenError> {
+        self.position = parse_fen(fen)?;
+        self.history.clear();
+        Ok(())
+    }
+
+    // for front-ends that keep the game themselves: `history` holds the hashes of the
+    // positions before `position`, so the search can see repetitions coming
+    pub fn set_game(&mut self, position: FenPosition, history: &[u64]) {
+        self.position = position;
+        self.history = history.to_vec();
+    }
+
+    pub fn fen(&self) -> String {
+        to_fen(&self.position)
+    }
+
+    // plays a move on the engine's board; illegal moves are refused
+    pub fn make_move(&mut self, mv: ChessMove) -> bool {
+        if self.position.board.legal(mv) {
+            self.history.push(self.position.board.get_hash());
+            self.position.make_move(mv);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    pub fn set_options(&mut self, options: SearchOptions) {
+        self.options = options;
+    }
+
+    pub fn hash_size(&self) -> usize {
+        self.transposition_table.size_mb()
+    }
+
+    // resizing throws the table's contents away, so it only happens when the size changes
+    pub fn set_hash_size(&mut self, hash_mb: usize) {
+        if self.transposition_table.size_mb() != hash_mb {
+            self.transposition_table.resize(hash_mb);
+        }
+    }
+
+    // forgets everything learned about earlier games
+    pub fn new_game(&mut self) {
+        self.position = FenPosition::default();
+        self.history.clear();
+        self.transposition_table.resize(self.transposition_table.size_mb());
+        self.stats = SearchStats::default();
+    }
+
+    // static evaluation of the current position, in centipawns from white's point of view
+    pub fn evaluate(&self) -> i32 {
+        evaluate_board(&self.position.board)
+    }
+
+    // the same evaluation split into its terms, for each side
+    pub fn evaluation_trace(&self) -> EvalTrace {
+        evaluation_trace(&self.position.board)
+    }
+
+    pub fn search(&mut self, limits: &SearchLimits, stop: &AtomicBool) -> SearchResult {
+        self.search_with_info(limits, stop, &mut |_, _| {})
+    }
+
+    // like `search`, calling `report` after every completed iteration
+    pub fn search_with_info(
+        &mut self,
+        limits: &SearchLimits,
+        stop: &AtomicBool,
+        report: &mut dyn FnMut(&SearchResult, &SearchStats),
+    ) -> SearchResult {
+        best_move(
+            &self.options,
+            &self.position,
+            &self.history,
+            limits,
+            &mut self.stats,
+            stop,
+            &mut self.transposition_table,
+            report,
+        )
+    }
+
+    // node counts, timing and table statistics of the last search
+    pub fn last_stats(&self) -> SearchStats {
+        self.stats
+    }
+}
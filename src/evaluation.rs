@@ -0,0 +1,234 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use chess::{Board, Color, Piece, Square};
+use crate::king_safety::{king_safety, king_score};
+use crate::piece_activity::{activity, piece_activity};
+use crate::pawn_structure::{evaluate_color, evaluate_pawns, passed_pawn_blockers, PawnEntry, PawnTable};
+use crate::piece_square_tables::{
+    EG_BISHOP_PST, EG_KING_PST, EG_KNIGHT_PST, EG_PAWN_PST, EG_QUEEN_PST, EG_ROOK_PST,
+    MG_BISHOP_PST, MG_KING_PST, MG_KNIGHT_PST, MG_PAWN_PST, MG_QUEEN_PST, MG_ROOK_PST,
+};
+
+// fase da partida: MAX_PHASE com todas as pecas no tabuleiro, 0 quando so sobram reis e peoes
+pub const MAX_PHASE: i32 = 24;
+
+// uma pontuacao em dois valores, um para o meio-jogo e outro para o final;
+// cada termo da avaliacao soma os dois e a fase decide quanto vale cada um
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Self { mg, eg }
+    }
+
+    // interpolacao linear entre os dois valores, sem saltos quando uma peca sai do tabuleiro
+    pub fn taper(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, factor: i32) -> Score {
+        Score::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
+
+// avaliacao estatica em centipeoes, do ponto de vista das brancas
+pub fn evaluate_board(board: &Board) -> i32 {
+    evaluate(board, evaluate_pawns(board))
+}
+
+// o mesmo que evaluate_board, com a estrutura de peoes vinda da tabela de peoes (usado pela busca)
+pub fn evaluate_with_pawn_table(board: &Board, pawn_table: &mut PawnTable) -> i32 {
+    evaluate(board, pawn_table.probe(board))
+}
+
+fn evaluate(board: &Board, pawns: PawnEntry) -> i32 {
+    let mut score = pawns.score + passed_pawn_blockers(board, pawns.passed) + king_safety(board) + piece_activity(board);
+
+    for square in *board.color_combined(Color::White) {
+        let piece = board.piece_on(square).unwrap();
+        score += material(piece);
+        score += piece_square_value(piece, square, Color::White);
+    }
+
+    for square in *board.color_combined(Color::Black) {
+        let piece = board.piece_on(square).unwrap();
+        score -= material(piece);
+        score -= piece_square_value(piece, square, Color::Black);
+    }
+
+    score.taper(game_phase(board))
+}
+
+// um termo da avaliacao, com o quanto ele vale para cada cor
+pub struct EvalTerm {
+    pub name: &'static str,
+    pub white: Score,
+    pub black: Score,
+}
+
+impl EvalTerm {
+    fn new(name: &'static str) -> Self {
+        Self { name, white: Score::default(), black: Score::default() }
+    }
+
+    fn add(&mut self, color: Color, score: Score) {
+        match color {
+            Color::White => self.white += score,
+            Color::Black => self.black += score,
+        }
+    }
+
+    // do ponto de vista das brancas
+    pub fn score(&self) -> Score {
+        self.white - self.black
+    }
+}
+
+// a avaliacao separada termo a termo, para mostrar de onde vem o numero de evaluate_board
+pub struct EvalTrace {
+    pub terms: Vec<EvalTerm>,
+    pub phase: i32,
+}
+
+impl EvalTrace {
+    // sempre igual a evaluate_board
+    pub fn total(&self) -> i32 {
+        let score = self.terms.iter().fold(Score::default(), |total, term| total + term.score());
+        score.taper(self.phase)
+    }
+}
+
+// o mesmo calculo de evaluate_board, cor por cor, sem a tabela de peoes
+pub fn evaluation_trace(board: &Board) -> EvalTrace {
+    let mut material_term = EvalTerm::new("Material");
+    let mut pst_terms = [
+        EvalTerm::new("Pawn PST"),
+        EvalTerm::new("Knight PST"),
+        EvalTerm::new("Bishop PST"),
+        EvalTerm::new("Rook PST"),
+        EvalTerm::new("Queen PST"),
+        EvalTerm::new("King PST"),
+    ];
+    let mut pawn_term = EvalTerm::new("Pawn structure");
+    let mut king_term = EvalTerm::new("King safety");
+    let mut activity_term = EvalTerm::new("Piece activity");
+
+    for color in [Color::White, Color::Black] {
+        for square in *board.color_combined(color) {
+            let piece = board.piece_on(square).unwrap();
+            material_term.add(color, material(piece));
+            pst_terms[piece.to_index()].add(color, piece_square_value(piece, square, color));
+        }
+
+        // passed_pawn_blockers eh do ponto de vista das brancas
+        let (structure, passed) = evaluate_color(board, color);
+        let blockers = passed_pawn_blockers(board, passed);
+        pawn_term.add(color, structure + if color == Color::White { blockers } else { -blockers });
+
+        king_term.add(color, king_score(board, color));
+        activity_term.add(color, activity(board, color));
+    }
+
+    let mut terms = vec![material_term];
+    terms.extend(pst_terms);
+    terms.extend([pawn_term, king_term, activity_term]);
+
+    EvalTrace { terms, phase: game_phase(board) }
+}
+
+// cavalos e bispos contam 1, torres 2 e damas 4; com promocoes a soma pode passar do maximo
+pub fn game_phase(board: &Board) -> i32 {
+    let minors = (board.pieces(Piece::Knight) | board.pieces(Piece::Bishop)).popcnt() as i32;
+    let rooks = board.pieces(Piece::Rook).popcnt() as i32;
+    let queens = board.pieces(Piece::Queen).popcnt() as i32;
+
+    (minors + 2 * rooks + 4 * queens).min(MAX_PHASE)
+}
+
+// valor de uma peca para a busca (poda delta): o maior entre meio-jogo e final
+pub fn piece_value(piece: Piece) -> i32 {
+    let value = material(piece);
+    value.mg.max(value.eg)
+}
+
+// peoes valem mais no final, quando viram dama com mais facilidade; cavalos, menos
+fn material(piece: Piece) -> Score {
+    match piece {
+        Piece::Pawn => Score::new(100, 130),
+        Piece::Knight => Score::new(320, 290),
+        Piece::Bishop => Score::new(330, 320),
+        Piece::Rook => Score::new(500, 530),
+        Piece::Queen => Score::new(900, 950),
+        Piece::King => Score::new(20000, 20000),
+    }
+}
+
+fn piece_square_value(piece: Piece, square: Square, color: Color) -> Score {
+    let index = table_index(square, color);
+
+    let (mg_table, eg_table) = match piece {
+        Piece::Pawn => (&MG_PAWN_PST, &EG_PAWN_PST),
+        Piece::Knight => (&MG_KNIGHT_PST, &EG_KNIGHT_PST),
+        Piece::Bishop => (&MG_BISHOP_PST, &EG_BISHOP_PST),
+        Piece::Rook => (&MG_ROOK_PST, &EG_ROOK_PST),
+        Piece::Queen => (&MG_QUEEN_PST, &EG_QUEEN_PST),
+        Piece::King => (&MG_KING_PST, &EG_KING_PST),
+    };
+
+    Score::new(mg_table[index], eg_table[index])
+}
+
+// as tabelas sao escritas do ponto de vista das brancas com a oitava fileira primeiro
+// (indice 0 = a8), enquanto em Square::to_index a1 eh 0 e h8 eh 63.
+// `^ 56` inverte so a fileira (a1 <-> a8), mantendo a coluna: eh a conversao para as brancas.
+// para as pretas a tabela tambem eh espelhada na vertical, o que desfaz a inversao
+fn table_index(square: Square, color: Color) -> usize {
+    match color {
+        Color::White => square.to_index() ^ 56,
+        Color::Black => square.to_index(),
+    }
+}
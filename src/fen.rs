@@ -0,0 +1,233 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chess::{
+    get_bishop_moves, get_king_moves, get_knight_moves, get_pawn_attacks, get_rook_moves, BitBoard, Board,
+    BoardBuilder, ChessMove, Color, File, Piece, Rank, Square, EMPTY, ALL_SQUARES,
+};
+
+// chess::Board keeps no move counters, so they travel next to it
+#[derive(Clone, Copy)]
+pub struct FenPosition {
+    pub board: Board,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl Default for FenPosition {
+    fn default() -> Self {
+        Self { board: Board::default(), halfmove_clock: 0, fullmove_number: 1 }
+    }
+}
+
+impl FenPosition {
+    // plays a move, keeping the counters the way a FEN expects them
+    pub fn make_move(&mut self, mv: ChessMove) {
+        let is_pawn_move = self.board.piece_on(mv.get_source()) == Some(Piece::Pawn);
+        let is_capture = self.board.piece_on(mv.get_dest()).is_some();
+
+        self.halfmove_clock = if is_pawn_move || is_capture { 0 } else { self.halfmove_clock + 1 };
+        if self.board.side_to_move() == Color::Black {
+            self.fullmove_number += 1;
+        }
+
+        self.board = self.board.make_move_new(mv);
+    }
+}
+
+#[derive(Debug)]
+pub enum FenError {
+    FieldCount(usize),
+    Counter(String),
+    Position(String),
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FenError::FieldCount(count) => write!(f, "a FEN has 4 or 6 fields, this one has {}", count),
+            FenError::Counter(counter) => write!(f, "invalid move counter: {}", counter),
+            FenError::Position(reason) => write!(f, "illegal position: {}", reason),
+        }
+    }
+}
+
+// parses the six FEN fields; the two move counters may be left out and default to "0 1"
+pub fn parse_fen(fen: &str) -> Result<FenPosition, FenError> {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+
+    if fields.len() != 4 && fields.len() != 6 {
+        return Err(FenError::FieldCount(fields.len()));
+    }
+
+    check_placement(fields[0])?;
+
+    let builder = BoardBuilder::from_str(&fields[..4].join(" ")).map_err(|error| FenError::Position(error.to_string()))?;
+
+    check_position(&builder)?;
+    check_en_passant(fields[3], &builder)?;
+
+    let board = Board::try_from(builder).map_err(|error| FenError::Position(error.to_string()))?;
+
+    let (halfmove_clock, fullmove_number) = if fields.len() == 6 {
+        let halfmove_clock = fields[4].parse().map_err(|_| FenError::Counter(fields[4].to_string()))?;
+        let fullmove_number = match fields[5].parse() {
+            Ok(number) if number >= 1 => number,
+            _ => return Err(FenError::Counter(fields[5].to_string())),
+        };
+        (halfmove_clock, fullmove_number)
+    } else {
+        (0, 1)
+    };
+
+    Ok(FenPosition { board, halfmove_clock, fullmove_number })
+}
+
+// the chess crate reads a board with missing or extra squares without complaint
+fn check_placement(placement: &str) -> Result<(), FenError> {
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::Position(format!("the board has {} ranks instead of 8", ranks.len())));
+    }
+
+    for (index, rank) in ranks.iter().enumerate() {
+        let squares: u32 = rank.chars().map(|c| c.to_digit(10).unwrap_or(1)).sum();
+        if squares != 8 {
+            return Err(FenError::Position(format!("rank {} has {} squares instead of 8", 8 - index, squares)));
+        }
+    }
+
+    Ok(())
+}
+
+// the chess crate's own sanity check runs too late to catch a missing king, lets through
+// impossible material, and explains a king left in check with a message about missing kings
+fn check_position(builder: &BoardBuilder) -> Result<(), FenError> {
+    for color in [Color::White, Color::Black] {
+        let kings = pieces(builder, Piece::King, color).popcnt();
+        if kings != 1 {
+            return Err(FenError::Position(format!("{:?} has {} kings", color, kings)));
+        }
+
+        let pawns = pieces(builder, Piece::Pawn, color);
+        if pawns.popcnt() > 8 {
+            return Err(FenError::Position(format!("{:?} has {} pawns", color, pawns.popcnt())));
+        }
+        for square in pawns {
+            if square.get_rank() == Rank::First || square.get_rank() == Rank::Eighth {
+                return Err(FenError::Position(format!("{:?} has a pawn on {}", color, square)));
+            }
+        }
+
+        let count = ALL_SQUARES.iter().filter(|square| builder[**square].is_some_and(|(_, c)| c == color)).count();
+        if count > 16 {
+            return Err(FenError::Position(format!("{:?} has {} pieces", color, count)));
+        }
+    }
+
+    let to_move = builder.get_side_to_move();
+    if is_in_check(builder, !to_move) {
+        return Err(FenError::Position(format!("{:?} is in check with {:?} to move", !to_move, to_move)));
+    }
+
+    check_castling(builder)
+}
+
+// a castling right needs the king and that side's rook still on their starting squares
+fn check_castling(builder: &BoardBuilder) -> Result<(), FenError> {
+    for color in [Color::White, Color::Black] {
+        let rights = builder.get_castle_rights(color);
+        let back_rank = color.to_my_backrank();
+
+        let mut needed = Vec::new();
+        if rights.has_kingside() {
+            needed.push(("kingside", File::H));
+        }
+        if rights.has_queenside() {
+            needed.push(("queenside", File::A));
+        }
+
+        for (side, rook_file) in needed {
+            let king = Square::make_square(back_rank, File::E);
+            if builder[king] != Some((Piece::King, color)) {
+                return Err(FenError::Position(format!("{:?} can castle {} without a king on {}", color, side, king)));
+            }
+
+            let rook = Square::make_square(back_rank, rook_file);
+            if builder[rook] != Some((Piece::Rook, color)) {
+                return Err(FenError::Position(format!("{:?} can castle {} without a rook on {}", color, side, rook)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// the chess crate keeps only the file of the en passant square and drops a field it can't read,
+// so the square is checked here: behind a pawn of the side that just moved, which moved two squares
+fn check_en_passant(field: &str, builder: &BoardBuilder) -> Result<(), FenError> {
+    if field == "-" {
+        return Ok(());
+    }
+
+    let square = Square::from_str(field)
+        .map_err(|_| FenError::Position(format!("the en passant square {} is not on the board", field)))?;
+
+    let to_move = builder.get_side_to_move();
+    let rank = match to_move {
+        Color::White => Rank::Sixth,
+        Color::Black => Rank::Third,
+    };
+    if square.get_rank() != rank {
+        return Err(FenError::Position(format!("the en passant square {} is not possible with {:?} to move", square, to_move)));
+    }
+
+    let pawn = Square::make_square((!to_move).to_fourth_rank(), square.get_file());
+    if builder[pawn] != Some((Piece::Pawn, !to_move)) {
+        return Err(FenError::Position(format!("the en passant square {} has no {:?} pawn in front of it", square, !to_move)));
+    }
+
+    Ok(())
+}
+
+fn pieces(builder: &BoardBuilder, piece: Piece, color: Color) -> BitBoard {
+    ALL_SQUARES
+        .iter()
+        .filter(|square| builder[**square] == Some((piece, color)))
+        .fold(EMPTY, |bitboard, square| bitboard | BitBoard::from_square(*square))
+}
+
+// whether any piece of the other color attacks `color`'s king
+fn is_in_check(builder: &BoardBuilder, color: Color) -> bool {
+    let king = pieces(builder, Piece::King, color).to_square();
+    let occupied = ALL_SQUARES
+        .iter()
+        .filter(|square| builder[**square].is_some())
+        .fold(EMPTY, |bitboard, square| bitboard | BitBoard::from_square(*square));
+    let enemy = |piece| pieces(builder, piece, !color);
+    let queens = enemy(Piece::Queen);
+
+    let attackers = get_pawn_attacks(king, color, enemy(Piece::Pawn))
+        | (get_knight_moves(king) & enemy(Piece::Knight))
+        | (get_bishop_moves(king, occupied) & (enemy(Piece::Bishop) | queens))
+        | (get_rook_moves(king, occupied) & (enemy(Piece::Rook) | queens))
+        | (get_king_moves(king) & enemy(Piece::King));
+
+    attackers != EMPTY
+}
+
+pub fn to_fen(position: &FenPosition) -> String {
+    // Board's Display already writes the first three fields; its fourth is the square of the pawn
+    // that can be taken en passant rather than the square behind it, and its counters are a fixed "0 1"
+    let board = &position.board;
+    let board_fen = board.to_string();
+    let fields: Vec<&str> = board_fen.split_whitespace().take(3).collect();
+
+    let en_passant = match board.en_passant().and_then(|pawn| pawn.forward(board.side_to_move())) {
+        Some(square) => square.to_string(),
+        None => "-".to_string(),
+    };
+
+    format!("{} {} {} {}", fields.join(" "), en_passant, position.halfmove_clock, position.fullmove_number)
+}
+
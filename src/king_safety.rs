@@ -0,0 +1,112 @@
+use chess::{
+    get_adjacent_files, get_bishop_moves, get_file, get_king_moves, get_knight_moves, get_rank, get_rook_moves,
+    BitBoard, Board, Color, File, Piece, Rank, Square, EMPTY,
+};
+use crate::evaluation::Score;
+
+// todos os termos so valem no meio-jogo: no final o rei precisa sair para o centro,
+// e a interpolacao pela fase faz eles sumirem aos poucos
+
+// peao amigo uma ou duas fileiras a frente do rei, na coluna dele ou nas vizinhas
+const SHIELD: [Score; 2] = [Score::new(12, 0), Score::new(6, 0)];
+// peao inimigo avancando na direcao do rei, pela distancia em fileiras ate ele
+const STORM: [Score; 5] = [Score::new(0, 0), Score::new(-5, 0), Score::new(-20, 0), Score::new(-10, 0), Score::new(-5, 0)];
+// coluna perto do rei sem peao nenhum, ou so com peao inimigo
+const OPEN_FILE: Score = Score::new(-25, 0);
+const HALF_OPEN_FILE: Score = Score::new(-12, 0);
+
+// peso de cada peca que ataca a zona do rei, por casa atacada
+const ATTACK_WEIGHT_KNIGHT: i32 = 2;
+const ATTACK_WEIGHT_BISHOP: i32 = 2;
+const ATTACK_WEIGHT_ROOK: i32 = 3;
+const ATTACK_WEIGHT_QUEEN: i32 = 5;
+const MAX_ATTACK_PENALTY: i32 = 500;
+
+// seguranca dos dois reis, do ponto de vista das brancas
+pub fn king_safety(board: &Board) -> Score {
+    king_score(board, Color::White) - king_score(board, Color::Black)
+}
+
+// seguranca do rei de uma cor, a favor dela
+pub fn king_score(board: &Board, color: Color) -> Score {
+    let king = board.king_square(color);
+    let pawns = board.pieces(Piece::Pawn);
+    let own_pawns = pawns & board.color_combined(color);
+    let enemy_pawns = pawns & board.color_combined(!color);
+
+    let mut score = Score::default();
+
+    // a coluna do rei e as vizinhas
+    let files = get_file(king.get_file()) | get_adjacent_files(king.get_file());
+    for distance in 1..=2 {
+        if let Some(rank) = rank_ahead(king, color, distance) {
+            let shield = (own_pawns & files & get_rank(rank)).popcnt() as i32;
+            score += SHIELD[distance - 1] * shield;
+        }
+    }
+    for (distance, penalty) in STORM.iter().enumerate().skip(1) {
+        if let Some(rank) = rank_ahead(king, color, distance) {
+            let storm = (enemy_pawns & files & get_rank(rank)).popcnt() as i32;
+            score += *penalty * storm;
+        }
+    }
+
+    let king_file = king.get_file().to_index();
+    for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+        let file = get_file(File::from_index(file));
+        if own_pawns & file == EMPTY {
+            score += if enemy_pawns & file == EMPTY { OPEN_FILE } else { HALF_OPEN_FILE };
+        }
+    }
+
+    score.mg -= attack_penalty(board, color, king);
+
+    score
+}
+
+// cada peca inimiga que ataca a zona do rei (o rei e as casas em volta) soma unidades de ataque
+// pelas casas atacadas; a penalidade cresce com o quadrado das unidades, e uma peca sozinha nao conta
+fn attack_penalty(board: &Board, color: Color, king: Square) -> i32 {
+    let zone = get_king_moves(king) | BitBoard::from_square(king);
+    let occupied = *board.combined();
+    let enemies = board.color_combined(!color);
+
+    let mut attackers = 0;
+    let mut units = 0;
+
+    for (piece, weight) in [
+        (Piece::Knight, ATTACK_WEIGHT_KNIGHT),
+        (Piece::Bishop, ATTACK_WEIGHT_BISHOP),
+        (Piece::Rook, ATTACK_WEIGHT_ROOK),
+        (Piece::Queen, ATTACK_WEIGHT_QUEEN),
+    ] {
+        for square in board.pieces(piece) & enemies {
+            let attacks = match piece {
+                Piece::Knight => get_knight_moves(square),
+                Piece::Bishop => get_bishop_moves(square, occupied),
+                Piece::Rook => get_rook_moves(square, occupied),
+                _ => get_bishop_moves(square, occupied) | get_rook_moves(square, occupied),
+            };
+
+            let attacked = (attacks & zone).popcnt() as i32;
+            if attacked > 0 {
+                attackers += 1;
+                units += weight * attacked;
+            }
+        }
+    }
+
+    if attackers < 2 {
+        return 0;
+    }
+
+    (units * units / 2).min(MAX_ATTACK_PENALTY)
+}
+
+// a fileira `distance` casas a frente do rei, no sentido em que os peoes da cor andam
+fn rank_ahead(king: Square, color: Color, distance: usize) -> Option<Rank> {
+    let rank = king.get_rank().to_index();
+    let index = if color == Color::White { rank + distance } else { rank.checked_sub(distance)? };
+
+    (index < 8).then(|| Rank::from_index(index))
+}
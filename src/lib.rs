@@ -0,0 +1,24 @@
+// the chess engine without any gui: search, evaluation and the uci front-end.
+// the egui window in main.rs is only one of its users
+
+pub mod best_move;
+pub mod draw;
+mod engine;
+pub mod evaluation;
+pub mod fen;
+pub mod king_safety;
+mod move_ordering;
+pub mod pawn_structure;
+pub mod piece_activity;
+pub mod pgn;
+pub mod piece_square_tables;
+pub mod transposition_table;
+pub mod uci;
+
+pub use best_move::{SearchLimits, SearchOptions, SearchResult, SearchStats};
+pub use draw::{draw_reason, DrawReason};
+pub use engine::{Engine, DEFAULT_HASH_MB};
+pub use evaluation::{EvalTerm, EvalTrace};
+pub use fen::{parse_fen, to_fen, FenError, FenPosition};
+pub use move_ordering::captured_piece;
+pub use pgn::{parse_pgn, PgnError, PgnGame, PgnResult};
@@ -1,16 +1,22 @@
 #![windows_subsystem = "windows"]
 
-mod best_move;
 mod uiboard;
 mod action_manager;
-mod piece_square_tables;
-use chess::{Board, ChessMove, Color, Piece, Square};
+mod replay;
+mod clock;
+use chess::{ChessMove, Color, Piece, Square};
 use eframe::egui::{self, FontId, RichText, Color32};
-use egui_extras;
 use std::ops::RangeInclusive;
-use std::time::Duration;
 use thousands::Separable;
-use action_manager::TurnStates;
+use action_manager::{OpponentReply, TurnStates};
+use replay::Replay;
+use clock::{Clocks, TIME_CONTROLS};
+use rust_chess_engine::best_move::format_score;
+use rust_chess_engine::pgn::PgnMove;
+use rust_chess_engine::{to_fen, Engine, FenPosition, PgnGame, SearchStats, DEFAULT_HASH_MB};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
 
 
 fn main() -> Result<(), eframe::Error> {
@@ -36,36 +42,95 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
+#[derive(PartialEq, Clone, Copy)]
+pub enum SearchMode {
+    FixedDepth,
+    FixedTime,
+}
+
+#[derive(PartialEq, Clone, Copy)]
+pub enum SideChoice {
+    White,
+    Black,
+    Random,
+}
+
 struct ChessApp {
-    board: Board,
-    count: i64,
+    position: FenPosition,
+    // the hash of every position of the game so far, for the repetition rule
+    position_history: Vec<u64>,
+    stats: SearchStats,
+    search_mode: SearchMode,
     depth: u32,
-    time_elapsed: Duration,
+    move_time_ms: u64,
+    // index into TIME_CONTROLS, used from the next game on
+    time_control: usize,
+    clocks: Option<Clocks>,
     pruning: bool,
+    move_ordering: bool,
+    contempt: i32,
+    hash_mb: usize,
+    engine: Arc<Mutex<Engine>>,
+    side_choice: SideChoice,
+    human_color: Color,
+    flip_board: bool,
     game_is_over: bool,
     turn_state: TurnStates,
     white_slain_pieces: Vec<Piece>,
     black_slain_pieces: Vec<Piece>,
     source_square: Option<Square>,
+    pending_promotion: Option<(Square, Square)>,
     legal_moves_from_source: Vec<ChessMove>,
-    winner: Option<Color>
+    principal_variation: Vec<ChessMove>,
+    winner: Option<Color>,
+    fen_input: String,
+    fen_error: Option<String>,
+    game_record: PgnGame,
+    undone_moves: Vec<PgnMove>,
+    pgn_path: String,
+    pgn_status: Option<String>,
+    replay: Option<Replay>,
+    opponent_reply: Option<Receiver<OpponentReply>>,
+    stop_search: Arc<AtomicBool>
 }
 
 impl Default for ChessApp {
     fn default() -> Self {
         Self {
-            board: Board::default(),
-            count: 0,
+            position: FenPosition::default(),
+            position_history: vec![FenPosition::default().board.get_hash()],
+            stats: SearchStats::default(),
+            search_mode: SearchMode::FixedDepth,
             depth: 3,
-            time_elapsed: Duration::ZERO,
+            move_time_ms: 1000,
+            time_control: 0,
+            clocks: None,
             pruning: true,
+            move_ordering: true,
+            contempt: 0,
+            hash_mb: DEFAULT_HASH_MB,
+            engine: Arc::new(Mutex::new(Engine::new(DEFAULT_HASH_MB))),
+            side_choice: SideChoice::White,
+            human_color: Color::White,
+            flip_board: false,
             game_is_over: false,
             turn_state: TurnStates::PieceSelection,
             white_slain_pieces: Vec::new(),
             black_slain_pieces: Vec::new(),
             source_square: None,
+            pending_promotion: None,
             legal_moves_from_source: Vec::new(),
-            winner: None
+            principal_variation: Vec::new(),
+            winner: None,
+            fen_input: String::new(),
+            fen_error: None,
+            game_record: PgnGame::new(FenPosition::default()),
+            undone_moves: Vec::new(),
+            pgn_path: String::new(),
+            pgn_status: None,
+            replay: None,
+            opponent_reply: None,
+            stop_search: Arc::new(AtomicBool::new(false))
         }
     }
 }
@@ -107,37 +172,167 @@ impl eframe::App for ChessApp {
                 ..Default::default()
             })
             .show(ctx, |ui| {
-                ui.label(RichText::new(format!("settings:")).font(FontId::proportional(35.0)));
+                ui.label(RichText::new("settings:").font(FontId::proportional(35.0)));
 
                 ui.label(RichText::new("").font(FontId::proportional(2.0)));
 
                 ui.horizontal(|ui| {
-                    ui.label(RichText::new("depth:").font(FontId::proportional(25.0)));
-                    ui.add(
-                        egui::widgets::DragValue::new(&mut self.depth)
-                            .speed(0.05)
-                            .clamp_range(RangeInclusive::new(1, 7)),
-                    );
+                    ui.radio_value(&mut self.search_mode, SearchMode::FixedDepth, "fixed depth");
+                    ui.radio_value(&mut self.search_mode, SearchMode::FixedTime, "fixed time");
+                });
+
+                match self.search_mode {
+                    SearchMode::FixedDepth => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("depth:").font(FontId::proportional(25.0)));
+                            ui.add(
+                                egui::widgets::DragValue::new(&mut self.depth)
+                                    .speed(0.05)
+                                    .clamp_range(RangeInclusive::new(1, 7)),
+                            );
+                        });
+                        ui.label(RichText::new(r"                                          /\ /\ /\ /\ /\").font(FontId::proportional(8.0)));
+                        ui.label(RichText::new("  (warning!! don't put it too high!)\n").font(FontId::proportional(12.0)).italics());
+                    },
+                    SearchMode::FixedTime => {
+                        ui.horizontal(|ui| {
+                            ui.label(RichText::new("time:").font(FontId::proportional(25.0)));
+                            ui.add(
+                                egui::widgets::DragValue::new(&mut self.move_time_ms)
+                                    .speed(10.0)
+                                    .clamp_range(RangeInclusive::new(100, 60000))
+                                    .suffix(" ms"),
+                            );
+                        });
+                        ui.label(RichText::new("  (per engine move)\n").font(FontId::proportional(12.0)).italics());
+                    },
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("clock:").font(FontId::proportional(25.0)));
+                    egui::ComboBox::from_id_source("time_control")
+                        .selected_text(TIME_CONTROLS[self.time_control].0)
+                        .show_ui(ui, |ui| {
+                            for (i, (name, _)) in TIME_CONTROLS.iter().enumerate() {
+                                ui.selectable_value(&mut self.time_control, i, *name);
+                            }
+                        });
                 });
-                ui.label(RichText::new(r"                                          /\ /\ /\ /\ /\").font(FontId::proportional(8.0)));
-                ui.label(RichText::new("  (warning!! don't put it too high!)\n").font(FontId::proportional(12.0)).italics());
+                if self.time_control != 0 {
+                    ui.label(RichText::new("  (from the next game; the engine\n  manages its own clock)\n").font(FontId::proportional(12.0)).italics());
+                }
 
                 ui.horizontal(|ui| {
                     ui.label(RichText::new("alpha-beta:").font(FontId::proportional(25.0)));
                     toggle_ui(ui, &mut self.pruning);
                 });
 
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("ordering:").font(FontId::proportional(25.0)));
+                    toggle_ui(ui, &mut self.move_ordering);
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("contempt:").font(FontId::proportional(25.0)));
+                    ui.add(
+                        egui::widgets::DragValue::new(&mut self.contempt)
+                            .speed(0.5)
+                            .clamp_range(RangeInclusive::new(-100, 100))
+                            .suffix(" cp"),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("hash:").font(FontId::proportional(25.0)));
+                    ui.add(
+                        egui::widgets::DragValue::new(&mut self.hash_mb)
+                            .speed(0.2)
+                            .clamp_range(RangeInclusive::new(1, 1024))
+                            .suffix(" MB"),
+                    );
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("play:").font(FontId::proportional(25.0)));
+                    ui.radio_value(&mut self.side_choice, SideChoice::White, "white");
+                    ui.radio_value(&mut self.side_choice, SideChoice::Black, "black");
+                });
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.side_choice, SideChoice::Random, "random");
+                    ui.label(RichText::new("(from the next game)").font(FontId::proportional(12.0)).italics());
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new("flip board:").font(FontId::proportional(25.0)));
+                    toggle_ui(ui, &mut self.flip_board);
+                });
+
                 ui.label(RichText::new("").font(FontId::proportional(5.0)));
 
                 ui.vertical_centered(|ui| {
+                    let thinking = matches!(self.turn_state, TurnStates::OpponentThinking);
+                    if ui.add_enabled(thinking, egui::Button::new("Move now")).clicked() {
+                        self.move_now();
+                    };
+
                     if ui.button("Reset").clicked() {
-                        self.board = Board::default();
-                        self.reset_info();
-                        self.game_is_over = false;
-                        self.winner = None;
-                        self.turn_state = TurnStates::PieceSelection;
+                        self.start_game(FenPosition::default());
                     };
-                });               
+                });
+
+                ui.horizontal(|ui| {
+                    let replaying = self.replay.is_some();
+
+                    if ui.add_enabled(!replaying && !self.game_record.moves.is_empty(), egui::Button::new("Undo")).clicked() {
+                        self.undo();
+                    };
+
+                    if ui.add_enabled(!replaying && !self.undone_moves.is_empty(), egui::Button::new("Redo")).clicked() {
+                        self.redo();
+                    };
+                });
+
+                ui.label(RichText::new("").font(FontId::proportional(5.0)));
+                ui.label(RichText::new("FEN:").font(FontId::proportional(25.0)));
+                ui.text_edit_singleline(&mut self.fen_input);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Load FEN").clicked() {
+                        self.load_fen();
+                    };
+
+                    if ui.button("Copy FEN").clicked() {
+                        self.fen_input = to_fen(&self.position);
+                        self.fen_error = None;
+                        ui.output_mut(|output| output.copied_text = self.fen_input.clone());
+                    };
+                });
+
+                if let Some(fen_error) = &self.fen_error {
+                    ui.label(RichText::new(fen_error).color(Color32::from_rgb(230, 110, 110)));
+                }
+
+                ui.label(RichText::new("").font(FontId::proportional(5.0)));
+                ui.label(RichText::new("PGN:").font(FontId::proportional(25.0)));
+                ui.text_edit_singleline(&mut self.pgn_path);
+
+                ui.horizontal(|ui| {
+                    if ui.button("Load PGN").clicked() {
+                        self.load_pgn();
+                    };
+
+                    if ui.button("Save PGN").clicked() {
+                        self.save_pgn();
+                    };
+                });
+
+                if self.replay.is_some() && ui.button("Back to game").clicked() {
+                    self.close_replay();
+                }
+
+                if let Some(pgn_status) = &self.pgn_status {
+                    ui.label(RichText::new(pgn_status).font(FontId::proportional(12.0)).italics());
+                }
             });
 
         egui::TopBottomPanel::top("top_panel")
@@ -146,18 +341,49 @@ impl eframe::App for ChessApp {
             .show_separator_line(false)
             .show(ctx, |ui| {
                 ui.vertical_centered( |ui| {
+                    if let Some(replay) = &self.replay {
+                        ui.label(RichText::new(format!(
+                            "\n{} vs {}  ({})",
+                            replay.game.tag("White").unwrap_or("?"),
+                            replay.game.tag("Black").unwrap_or("?"),
+                            replay.game.result.as_str()
+                        )).font(FontId::proportional(35.0)));
+
+                        let current_move = match replay.current_move() {
+                            Some(pgn_move) => match &pgn_move.comment {
+                                Some(comment) => format!("{} {{{}}}", pgn_move.san, comment),
+                                None => pgn_move.san.clone(),
+                            },
+                            None => "start".to_string(),
+                        };
+                        ui.label(RichText::new(format!(
+                            "ply {}/{}: {}",
+                            replay.ply,
+                            replay.last_ply(),
+                            current_move
+                        )).font(FontId::proportional(18.0)));
+                        return;
+                    }
+
+                    if matches!(self.turn_state, TurnStates::OpponentThinking) {
+                        ui.label(RichText::new("\nthinking...").font(FontId::proportional(35.0)));
+                        ui.add(egui::Spinner::new().size(25.0));
+                        return;
+                    }
+
                     ui.label(
                         if !self.game_is_over{
                                 RichText::new(format!(
-                                    "\n{} nodes searched in {}.{} seconds",
-                                    self.count.separate_with_commas(),
-                                    self.time_elapsed.as_secs(),
-                                    self.time_elapsed.subsec_millis()
-                                ))
-                            } else if self.game_is_over && self.winner == None{
-                                RichText::new(format!(
-                                    "\nit's a draw!",
+                                    "\n{} nodes searched in {}.{:03} seconds (depth {})",
+                                    self.stats.count.separate_with_commas(),
+                                    self.stats.time_elapsed.as_secs(),
+                                    self.stats.time_elapsed.subsec_millis(),
+                                    self.stats.depth
                                 ))
+                            } else if let TurnStates::Draw(reason) = self.turn_state {
+                                RichText::new(format!("\ndraw by {}!", reason))
+                            } else if let TurnStates::OutOfTime(loser) = self.turn_state {
+                                RichText::new(format!("\n{:#?} won on time!", !loser))
                             } else {
                                 RichText::new(format!(
                                     "\n{:#?} won!",
@@ -167,6 +393,23 @@ impl eframe::App for ChessApp {
                         
                         .font(FontId::proportional(35.0)),
                     );
+
+                    if !self.game_is_over && self.stats.depth > 0 {
+                        let line: Vec<String> = self.principal_variation.iter().map(|mv| mv.to_string()).collect();
+
+                        ui.label(RichText::new(format!(
+                            "{}   line: {}",
+                            format_score(self.stats.score),
+                            line.join(" ")
+                        )).font(FontId::proportional(18.0)));
+
+                        ui.label(RichText::new(format!(
+                            "quiescence: {} nodes   tt: {} hits, {} overwrites",
+                            self.stats.qnodes.separate_with_commas(),
+                            self.stats.tt_hits.separate_with_commas(),
+                            self.stats.tt_overwrites.separate_with_commas()
+                        )).font(FontId::proportional(14.0)));
+                    }
                 });
             });
 
@@ -185,11 +428,19 @@ impl eframe::App for ChessApp {
                 &tiles
             );
 
+            self.display_clocks(ui, &tiles);
+
+            // the game is left alone while a replay is on the board
+            if self.replay.is_some() {
+                self.replay_controls(ui, &tiles);
+                return;
+            }
+
             self.display_possible_actions(ui,
                 &tiles,
                 &icons);
 
-            self.action_manager(ui, &tiles);
+            self.action_manager(ui, &tiles, &pieces);
             
         });
     }
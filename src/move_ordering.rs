@@ -0,0 +1,115 @@
+use chess::{Board, ChessMove, Piece};
+use crate::best_move::MAX_DEPTH;
+
+const TT_MOVE_SCORE: i32 = 1_000_000;
+const CAPTURE_SCORE: i32 = 100_000;
+const PROMOTION_SCORE: i32 = 90_000;
+const KILLER_SCORE: i32 = 80_000;
+
+// heuristicas de ordenacao de movimentos: quanto antes o melhor movimento
+// for testado, mais cedo acontecem os cortes alpha-beta
+pub struct MoveOrdering {
+    // dois movimentos silenciosos por ply que ja causaram corte
+    killers: [[Option<ChessMove>; 2]; MAX_DEPTH as usize + 1],
+    // pontuacao acumulada de movimentos silenciosos que causaram corte, indexada por [origem][destino]
+    history: [[i32; 64]; 64],
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            killers: [[None; 2]; MAX_DEPTH as usize + 1],
+            history: [[0; 64]; 64],
+        }
+    }
+
+    // ordem: movimento da tabela de transposicao, capturas (MVV-LVA), promocoes, killers e historico
+    pub fn order_moves(&self, board: &Board, moves: &mut [ChessMove], tt_move: Option<ChessMove>, ply: u32) {
+        moves.sort_by_cached_key(|mv| -self.score_move(board, *mv, tt_move, ply));
+    }
+
+    fn score_move(&self, board: &Board, mv: ChessMove, tt_move: Option<ChessMove>, ply: u32) -> i32 {
+        if Some(mv) == tt_move {
+            return TT_MOVE_SCORE;
+        }
+
+        if let Some(victim) = captured_piece(board, mv) {
+            return CAPTURE_SCORE + mvv_lva(victim, board.piece_on(mv.get_source()).unwrap());
+        }
+
+        if let Some(promotion) = mv.get_promotion() {
+            return PROMOTION_SCORE + ordering_value(promotion);
+        }
+
+        if let Some(killers) = self.killers.get(ply as usize) {
+            if killers[0] == Some(mv) {
+                return KILLER_SCORE;
+            }
+            if killers[1] == Some(mv) {
+                return KILLER_SCORE - 1;
+            }
+        }
+
+        self.history[mv.get_source().to_index()][mv.get_dest().to_index()]
+    }
+
+    // chamado quando um movimento silencioso causa um corte beta
+    pub fn record_cutoff(&mut self, board: &Board, mv: ChessMove, depth: u32, ply: u32) {
+        if is_noisy(board, mv) {
+            return;
+        }
+
+        if let Some(killers) = self.killers.get_mut(ply as usize) {
+            if killers[0] != Some(mv) {
+                killers[1] = killers[0];
+                killers[0] = Some(mv);
+            }
+        }
+
+        let entry = &mut self.history[mv.get_source().to_index()][mv.get_dest().to_index()];
+        *entry += (depth * depth) as i32;
+
+        // mantem o historico abaixo da pontuacao dos killers
+        if *entry >= KILLER_SCORE / 2 {
+            self.history.iter_mut().flatten().for_each(|value| *value /= 2);
+        }
+    }
+}
+
+// capturas e promocoes ja sao ordenadas por conta propria
+pub fn is_noisy(board: &Board, mv: ChessMove) -> bool {
+    captured_piece(board, mv).is_some() || mv.get_promotion().is_some()
+}
+
+// peca capturada pelo movimento, incluindo o peao capturado en passant
+pub fn captured_piece(board: &Board, mv: ChessMove) -> Option<Piece> {
+    match board.piece_on(mv.get_dest()) {
+        Some(piece) => Some(piece),
+        None => {
+            let is_pawn = board.piece_on(mv.get_source()) == Some(Piece::Pawn);
+            let changes_file = mv.get_source().get_file() != mv.get_dest().get_file();
+
+            if is_pawn && changes_file {
+                Some(Piece::Pawn)
+            } else {
+                None
+            }
+        },
+    }
+}
+
+// most valuable victim, least valuable attacker: PxQ antes de QxP
+pub fn mvv_lva(victim: Piece, attacker: Piece) -> i32 {
+    ordering_value(victim) * 10 - ordering_value(attacker)
+}
+
+fn ordering_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn => 1,
+        Piece::Knight => 3,
+        Piece::Bishop => 3,
+        Piece::Rook => 5,
+        Piece::Queen => 9,
+        Piece::King => 10,
+    }
+}
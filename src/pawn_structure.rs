@@ -0,0 +1,194 @@
+use chess::{get_adjacent_files, get_file, get_pawn_attacks, get_rank, BitBoard, Board, Color, Piece, Rank, Square, ALL_FILES, EMPTY};
+use crate::evaluation::Score;
+
+// penalidade por peao a mais na mesma coluna
+const DOUBLED: Score = Score::new(-10, -20);
+// nenhum peao amigo nas colunas vizinhas
+const ISOLATED: Score = Score::new(-15, -20);
+// os vizinhos ja passaram dele e a casa da frente eh atacada por um peao inimigo
+const BACKWARD: Score = Score::new(-10, -15);
+// defendido por outro peao
+const SUPPORTED: Score = Score::new(10, 15);
+// lado a lado com outro peao na mesma fileira
+const PHALANX: Score = Score::new(5, 10);
+
+// bonus do peao passado pela fileira relativa (0 = primeira fileira de quem tem o peao);
+// no final um peao passado vale muito mais
+const PASSED: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(5, 10),
+    Score::new(10, 20),
+    Score::new(15, 35),
+    Score::new(25, 60),
+    Score::new(40, 100),
+    Score::new(60, 150),
+    Score::new(0, 0),
+];
+
+// tamanho da tabela de peoes, em entradas
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+// estrutura de peoes de uma posicao, do ponto de vista das brancas.
+// so depende dos peoes, por isso pode ser guardada pelos peoes de cada cor;
+// o bloqueio dos peoes passados depende das outras pecas e fica de fora (ver passed_pawn_blockers)
+#[derive(Clone, Copy)]
+pub struct PawnEntry {
+    key: PawnKey,
+    pub score: Score,
+    // peoes passados das duas cores
+    pub passed: BitBoard,
+}
+
+// os peoes brancos e os pretos. Board::get_pawn_hash nao esta implementado no crate chess
+// (sempre devolve 0), entao a chave sao os proprios bitboards, sem risco de colisao
+type PawnKey = (BitBoard, BitBoard);
+
+// tabela de tamanho fixo indexada por um hash dos peoes;
+// a estrutura de peoes muda pouco durante uma busca, entao quase toda consulta acerta
+pub struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PawnTable {
+    pub fn new() -> Self {
+        Self { entries: vec![None; PAWN_TABLE_SIZE] }
+    }
+
+    // devolve a entrada guardada ou calcula e guarda uma nova, substituindo a anterior
+    pub fn probe(&mut self, board: &Board) -> PawnEntry {
+        let key = pawn_key(board);
+        let index = (pawn_hash(key) % self.entries.len() as u64) as usize;
+
+        match self.entries[index] {
+            Some(entry) if entry.key == key => entry,
+            _ => {
+                let entry = evaluate_pawns(board);
+                self.entries[index] = Some(entry);
+                entry
+            },
+        }
+    }
+}
+
+pub fn evaluate_pawns(board: &Board) -> PawnEntry {
+    let (white_score, white_passed) = evaluate_color(board, Color::White);
+    let (black_score, black_passed) = evaluate_color(board, Color::Black);
+
+    PawnEntry {
+        key: pawn_key(board),
+        score: white_score - black_score,
+        passed: white_passed | black_passed,
+    }
+}
+
+fn pawn_key(board: &Board) -> PawnKey {
+    let pawns = board.pieces(Piece::Pawn);
+    (pawns & board.color_combined(Color::White), pawns & board.color_combined(Color::Black))
+}
+
+// mistura os dois bitboards por multiplicacao; os bits altos sao os mais bem misturados
+fn pawn_hash((white, black): PawnKey) -> u64 {
+    let hash = white.0.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ black.0.wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+    hash >> 32
+}
+
+// um peao passado com qualquer peca na casa da frente perde metade do bonus
+pub fn passed_pawn_blockers(board: &Board, passed: BitBoard) -> Score {
+    let mut score = Score::default();
+
+    for square in passed {
+        let color = if board.color_combined(Color::White) & BitBoard::from_square(square) != EMPTY {
+            Color::White
+        } else {
+            Color::Black
+        };
+
+        let blocked = square
+            .forward(color)
+            .is_some_and(|stop| board.piece_on(stop).is_some());
+
+        if blocked {
+            let bonus = PASSED[relative_rank(square, color)];
+            let penalty = Score::new(bonus.mg / 2, bonus.eg / 2);
+            if color == Color::White { score -= penalty } else { score += penalty }
+        }
+    }
+
+    score
+}
+
+// pontuacao dos peoes de uma cor e quais deles sao passados
+pub fn evaluate_color(board: &Board, color: Color) -> (Score, BitBoard) {
+    let pawns = board.pieces(Piece::Pawn);
+    let own_pawns = pawns & board.color_combined(color);
+    let enemy_pawns = pawns & board.color_combined(!color);
+
+    let mut score = Score::default();
+    let mut passed = EMPTY;
+
+    for file in ALL_FILES {
+        let count = (own_pawns & get_file(file)).popcnt() as i32;
+        if count > 1 {
+            score += DOUBLED * (count - 1);
+        }
+    }
+
+    for square in own_pawns {
+        let file = get_file(square.get_file());
+        let neighbours = get_adjacent_files(square.get_file());
+        let ahead = ranks_ahead(square, color);
+
+        let isolated = own_pawns & neighbours == EMPTY;
+        // peao defendido: algum peao amigo ataca esta casa
+        let supported = get_pawn_attacks(square, !color, own_pawns) != EMPTY;
+        let phalanx = own_pawns & neighbours & get_rank(square.get_rank()) != EMPTY;
+
+        if isolated {
+            score += ISOLATED;
+        } else if own_pawns & neighbours & !ahead == EMPTY {
+            let stop_attacked = square
+                .forward(color)
+                .is_some_and(|stop| get_pawn_attacks(stop, color, enemy_pawns) != EMPTY);
+            if stop_attacked {
+                score += BACKWARD;
+            }
+        }
+
+        if supported {
+            score += SUPPORTED;
+        }
+        if phalanx {
+            score += PHALANX;
+        }
+
+        // sem peoes inimigos a frente na propria coluna e nas vizinhas; o de tras
+        // de dois peoes dobrados nao conta, o da frente ja leva o bonus
+        let is_passed = enemy_pawns & (file | neighbours) & ahead == EMPTY && own_pawns & file & ahead == EMPTY;
+        if is_passed {
+            score += PASSED[relative_rank(square, color)];
+            passed |= BitBoard::from_square(square);
+        }
+    }
+
+    (score, passed)
+}
+
+// fileira contada a partir do lado da cor (0 = primeira fileira dela)
+pub fn relative_rank(square: Square, color: Color) -> usize {
+    let rank = square.get_rank().to_index();
+    if color == Color::White { rank } else { 7 - rank }
+}
+
+// todas as fileiras a frente da casa, no sentido em que os peoes da cor andam
+pub fn ranks_ahead(square: Square, color: Color) -> BitBoard {
+    let rank = square.get_rank().to_index();
+    let ranks = if color == Color::White { rank + 1..8 } else { 0..rank };
+
+    ranks.fold(EMPTY, |mask, index| mask | get_rank(Rank::from_index(index)))
+}
@@ -0,0 +1,473 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use chess::{Board, BoardStatus, ChessMove, Color, MoveGen, Piece, Square};
+use crate::best_move::mate_distance;
+use crate::fen::{parse_fen, to_fen, FenError, FenPosition};
+use crate::move_ordering::captured_piece;
+
+// the tags every pgn must have, in this order
+const SEVEN_TAG_ROSTER: [&str; 7] = ["Event", "Site", "Date", "Round", "White", "Black", "Result"];
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Unfinished,
+}
+
+impl PgnResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PgnResult::WhiteWins => "1-0",
+            PgnResult::BlackWins => "0-1",
+            PgnResult::Draw => "1/2-1/2",
+            PgnResult::Unfinished => "*",
+        }
+    }
+
+    fn parse(result: &str) -> Option<Self> {
+        match result {
+            "1-0" => Some(PgnResult::WhiteWins),
+            "0-1" => Some(PgnResult::BlackWins),
+            "1/2-1/2" => Some(PgnResult::Draw),
+            "*" => Some(PgnResult::Unfinished),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum PgnError {
+    Tag(String),
+    Fen(FenError),
+    IllegalMove { ply: usize, san: String },
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PgnError::Tag(line) => write!(f, "malformed tag: {}", line),
+            PgnError::Fen(error) => write!(f, "bad FEN tag: {}", error),
+            PgnError::IllegalMove { ply, san } => write!(f, "illegal move at ply {}: {}", ply + 1, san),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PgnMove {
+    pub mv: ChessMove,
+    pub san: String,
+    pub comment: Option<String>,
+}
+
+// a game as it goes into a pgn file: tags, the starting position and the moves played from it
+#[derive(Clone)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>,
+    pub start: FenPosition,
+    pub moves: Vec<PgnMove>,
+    pub result: PgnResult,
+}
+
+impl PgnGame {
+    pub fn new(start: FenPosition) -> Self {
+        let mut game = Self {
+            tags: Vec::new(),
+            start,
+            moves: Vec::new(),
+            result: PgnResult::Unfinished,
+        };
+
+        for name in SEVEN_TAG_ROSTER {
+            game.set_tag(name, "?");
+        }
+        game.set_tag("Date", &pgn_date(SystemTime::now()));
+        game.set_tag("Result", PgnResult::Unfinished.as_str());
+
+        game
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter().find(|(tag, _)| tag == name).map(|(_, value)| value.as_str())
+    }
+
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        match self.tags.iter_mut().find(|(tag, _)| tag == name) {
+            Some((_, old_value)) => *old_value = value.to_string(),
+            None => self.tags.push((name.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn remove_tag(&mut self, name: &str) {
+        self.tags.retain(|(tag, _)| tag != name);
+    }
+
+    pub fn set_result(&mut self, result: PgnResult) {
+        self.result = result;
+        self.set_tag("Result", result.as_str());
+    }
+
+    // `board` is the position the move is played from, needed to write its SAN
+    pub fn push_move(&mut self, board: &Board, mv: ChessMove, comment: Option<String>) {
+        self.moves.push(PgnMove { mv, san: move_to_san(board, mv), comment });
+    }
+
+    // export format: roster first, then the other tags, then the movetext wrapped at 80 columns
+    pub fn to_pgn(&self) -> String {
+        let mut pgn = String::new();
+        let starts_from_fen = self.start.board != Board::default() || self.start.fullmove_number != 1;
+
+        for name in SEVEN_TAG_ROSTER {
+            pgn += &format!("[{} \"{}\"]\n", name, escape(self.tag(name).unwrap_or("?")));
+        }
+        if starts_from_fen {
+            pgn += "[SetUp \"1\"]\n";
+            pgn += &format!("[FEN \"{}\"]\n", to_fen(&self.start));
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.contains(&name.as_str()) && name != "SetUp" && name != "FEN" {
+                pgn += &format!("[{} \"{}\"]\n", name, escape(value));
+            }
+        }
+        pgn += "\n";
+
+        let mut tokens = Vec::new();
+        let mut position = self.start;
+
+        for (i, pgn_move) in self.moves.iter().enumerate() {
+            let white_to_move = position.board.side_to_move() == Color::White;
+
+            if white_to_move {
+                tokens.push(format!("{}.", position.fullmove_number));
+            } else if i == 0 {
+                tokens.push(format!("{}...", position.fullmove_number));
+            }
+
+            tokens.push(pgn_move.san.clone());
+            if let Some(comment) = &pgn_move.comment {
+                tokens.push(format!("{{{}}}", comment.replace('}', ")")));
+            }
+
+            position.make_move(pgn_move.mv);
+        }
+        tokens.push(self.result.as_str().to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() > 80 {
+                pgn += &line;
+                pgn += "\n";
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        pgn += &line;
+        pgn += "\n";
+
+        pgn
+    }
+}
+
+// reads the first game of a pgn file. comments stay attached to the move before them,
+// NAGs, move suffixes like "!?" and variations are skipped
+pub fn parse_pgn(text: &str) -> Result<PgnGame, PgnError> {
+    let mut tags = Vec::new();
+    let mut movetext = String::new();
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+
+        if trimmed.starts_with('[') && movetext.trim().is_empty() {
+            tags.push(parse_tag(trimmed).ok_or_else(|| PgnError::Tag(trimmed.to_string()))?);
+        } else if !trimmed.starts_with('%') {
+            movetext += line;
+            movetext.push('\n');
+        }
+    }
+
+    let start = match tags.iter().find(|(name, _)| name == "FEN") {
+        Some((_, fen)) => parse_fen(fen).map_err(PgnError::Fen)?,
+        None => FenPosition::default(),
+    };
+
+    let mut game = PgnGame { tags, start, moves: Vec::new(), result: PgnResult::Unfinished };
+    if let Some(result) = game.tag("Result").and_then(PgnResult::parse) {
+        game.result = result;
+    }
+
+    let mut position = start;
+    let mut chars = movetext.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                add_comment(&mut game, &comment);
+            },
+            ';' => {
+                let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                add_comment(&mut game, &comment);
+            },
+            '(' => {
+                // variations may nest and hold comments with parentheses in them
+                let mut depth = 1;
+                while depth > 0 {
+                    match chars.next() {
+                        Some('(') => depth += 1,
+                        Some(')') => depth -= 1,
+                        Some('{') => while chars.next().is_some_and(|c| c != '}') {},
+                        Some(';') => while chars.next().is_some_and(|c| c != '\n') {},
+                        Some(_) => (),
+                        None => break,
+                    }
+                }
+            },
+            '$' => while chars.next_if(|c| c.is_ascii_digit()).is_some() {},
+            c if c.is_whitespace() || c == ')' => (),
+            c => {
+                let mut token = c.to_string();
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && !"{}();$".contains(*c)) {
+                    token.push(c);
+                }
+
+                if let Some(result) = PgnResult::parse(&token) {
+                    game.set_result(result);
+                    break;
+                }
+
+                // move numbers, possibly glued to the move: "12.", "12...", "12.Nf3"
+                let digits = token.len() - token.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+                let san = if token[digits..].starts_with('.') {
+                    token[digits..].trim_start_matches('.')
+                } else {
+                    &token
+                };
+                if san.is_empty() {
+                    continue;
+                }
+
+                let ply = game.moves.len();
+                let mv = san_to_move(&position.board, san)
+                    .ok_or_else(|| PgnError::IllegalMove { ply, san: san.to_string() })?;
+
+                game.push_move(&position.board, mv, None);
+                position.make_move(mv);
+            },
+        }
+    }
+
+    Ok(game)
+}
+
+// the legal move a SAN string stands for; also takes "0-0", missing "x" or "=", and extra disambiguation
+pub fn san_to_move(board: &Board, san: &str) -> Option<ChessMove> {
+    let san = san.trim_end_matches(['+', '#', '!', '?']);
+
+    if san == "O-O" || san == "0-0" || san == "O-O-O" || san == "0-0-0" {
+        let kingside = san.len() == 3;
+        return MoveGen::new_legal(board).find(|mv| {
+            let source = mv.get_source().get_file().to_index();
+            let dest = mv.get_dest().get_file().to_index();
+            board.piece_on(mv.get_source()) == Some(Piece::King)
+                && source.abs_diff(dest) == 2
+                && (dest > source) == kingside
+        });
+    }
+
+    let mut chars: Vec<char> = san.chars().filter(|c| *c != 'x' && *c != '-' && *c != '=').collect();
+
+    let piece = match chars.first().copied().and_then(letter_piece) {
+        Some(piece) => {
+            chars.remove(0);
+            piece
+        },
+        None => Piece::Pawn,
+    };
+
+    let promotion = match chars.last().copied().and_then(letter_piece) {
+        Some(promotion) => {
+            chars.pop();
+            Some(promotion)
+        },
+        None => None,
+    };
+
+    if chars.len() < 2 {
+        return None;
+    }
+    let dest: String = chars.split_off(chars.len() - 2).into_iter().collect();
+    let dest = MoveGen::new_legal(board).map(|mv| mv.get_dest()).find(|square| square.to_string() == dest)?;
+
+    // whatever is left is the disambiguation: a file, a rank or both
+    let from_file = chars.iter().find(|c| c.is_ascii_lowercase()).map(|c| *c as usize - 'a' as usize);
+    let from_rank = match chars.iter().find_map(|c| c.to_digit(10)) {
+        Some(rank @ 1..=8) => Some(rank as usize - 1),
+        Some(_) => return None,
+        None => None,
+    };
+
+    let mut candidates = MoveGen::new_legal(board).filter(|mv| {
+        mv.get_dest() == dest
+            && mv.get_promotion() == promotion
+            && board.piece_on(mv.get_source()) == Some(piece)
+            && from_file.is_none_or(|file| mv.get_source().get_file().to_index() == file)
+            && from_rank.is_none_or(|rank| mv.get_source().get_rank().to_index() == rank)
+    });
+
+    match (candidates.next(), candidates.next()) {
+        (Some(mv), None) => Some(mv),
+        _ => None,
+    }
+}
+
+// standard algebraic notation, e.g. "Nbd2", "exd6", "e8=Q+", "O-O-O#"
+pub fn move_to_san(board: &Board, mv: ChessMove) -> String {
+    let source = mv.get_source();
+    let dest = mv.get_dest();
+    let piece = board.piece_on(source).unwrap();
+    let mut san = String::new();
+
+    let file_distance = (source.get_file().to_index() as i32 - dest.get_file().to_index() as i32).abs();
+
+    if piece == Piece::King && file_distance == 2 {
+        san += if dest.get_file().to_index() > source.get_file().to_index() { "O-O" } else { "O-O-O" };
+    } else {
+        let is_capture = captured_piece(board, mv).is_some();
+
+        if piece == Piece::Pawn {
+            if is_capture {
+                san.push(file_letter(source));
+            }
+        } else {
+            san.push(piece_letter(piece));
+
+            // another piece of the same kind that could go to the same square
+            let rivals: Vec<ChessMove> = MoveGen::new_legal(board)
+                .filter(|other| {
+                    other.get_dest() == dest
+                        && other.get_source() != source
+                        && board.piece_on(other.get_source()) == Some(piece)
+                })
+                .collect();
+
+            if !rivals.is_empty() {
+                let same_file = rivals.iter().any(|other| other.get_source().get_file() == source.get_file());
+                let same_rank = rivals.iter().any(|other| other.get_source().get_rank() == source.get_rank());
+
+                if !same_file {
+                    san.push(file_letter(source));
+                } else if !same_rank {
+                    san.push(rank_digit(source));
+                } else {
+                    san.push(file_letter(source));
+                    san.push(rank_digit(source));
+                }
+            }
+        }
+
+        if is_capture {
+            san.push('x');
+        }
+        san += &dest.to_string();
+
+        if let Some(promotion) = mv.get_promotion() {
+            san.push('=');
+            san.push(piece_letter(promotion));
+        }
+    }
+
+    let after = board.make_move_new(mv);
+    if after.status() == BoardStatus::Checkmate {
+        san.push('#');
+    } else if after.checkers().popcnt() > 0 {
+        san.push('+');
+    }
+
+    san
+}
+
+// engine comment for a move, from white's point of view: "+0.35/5" or "-M3/7"
+pub fn engine_comment(score: i32, depth: u32) -> String {
+    match mate_distance(score) {
+        Some(moves) if moves > 0 => format!("+M{}/{}", moves, depth),
+        Some(moves) => format!("-M{}/{}", -moves, depth),
+        None => format!("{:+.2}/{}", score as f32 / 100.0, depth),
+    }
+}
+
+// "YYYY.MM.DD" in utc, as the Date tag wants it
+pub fn pgn_date(time: SystemTime) -> String {
+    let days = time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs() / 86400) as i64;
+
+    // days since 1970-01-01 to a civil date (howard hinnant's algorithm)
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// `[Name "value"]`, undoing the escapes `escape` adds
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?.trim();
+    let (name, value) = inner.split_once(char::is_whitespace)?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+fn add_comment(game: &mut PgnGame, comment: &str) {
+    let comment = comment.split_whitespace().collect::<Vec<&str>>().join(" ");
+
+    if let Some(last) = game.moves.last_mut() {
+        last.comment = Some(match last.comment.take() {
+            Some(old) => format!("{} {}", old, comment),
+            None => comment,
+        });
+    }
+}
+
+fn piece_letter(piece: Piece) -> char {
+    match piece {
+        Piece::Pawn => 'P',
+        Piece::Knight => 'N',
+        Piece::Bishop => 'B',
+        Piece::Rook => 'R',
+        Piece::Queen => 'Q',
+        Piece::King => 'K',
+    }
+}
+
+fn letter_piece(letter: char) -> Option<Piece> {
+    match letter {
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
+
+fn file_letter(square: Square) -> char {
+    (b'a' + square.get_file().to_index() as u8) as char
+}
+
+fn rank_digit(square: Square) -> char {
+    (b'1' + square.get_rank().to_index() as u8) as char
+}
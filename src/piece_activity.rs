@@ -0,0 +1,93 @@
+use chess::{
+    get_adjacent_files, get_bishop_moves, get_file, get_knight_moves, get_pawn_attacks, get_rank, get_rook_moves,
+    BitBoard, Board, Color, Piece, Rank, Square, EMPTY,
+};
+use crate::evaluation::Score;
+use crate::pawn_structure::{ranks_ahead, relative_rank};
+
+// mobilidade: pontos por casa alcancada alem de uma mobilidade "normal" para a peca,
+// entao uma peca presa perde pontos e uma ativa ganha
+const KNIGHT_MOBILITY: (Score, i32) = (Score::new(4, 4), 4);
+const BISHOP_MOBILITY: (Score, i32) = (Score::new(5, 5), 7);
+const ROOK_MOBILITY: (Score, i32) = (Score::new(2, 4), 7);
+const QUEEN_MOBILITY: (Score, i32) = (Score::new(1, 2), 14);
+
+const BISHOP_PAIR: Score = Score::new(30, 50);
+// coluna sem peoes, ou so com peoes inimigos
+const ROOK_OPEN_FILE: Score = Score::new(25, 10);
+const ROOK_HALF_OPEN_FILE: Score = Score::new(12, 6);
+// so conta com o rei inimigo preso na ultima fileira ou peoes inimigos para atacar na setima
+const ROOK_ON_SEVENTH: Score = Score::new(20, 30);
+// cavalo avancado, defendido por peao e que nenhum peao inimigo consegue expulsar
+const KNIGHT_OUTPOST: Score = Score::new(20, 10);
+
+// atividade das pecas das duas cores, do ponto de vista das brancas
+pub fn piece_activity(board: &Board) -> Score {
+    activity(board, Color::White) - activity(board, Color::Black)
+}
+
+// atividade das pecas de uma cor, a favor dela
+pub fn activity(board: &Board, color: Color) -> Score {
+    let occupied = *board.combined();
+    let own = board.color_combined(color);
+    let pawns = board.pieces(Piece::Pawn);
+    let own_pawns = pawns & own;
+    let enemy_pawns = pawns & board.color_combined(!color);
+
+    // casas ocupadas pelas proprias pecas ou atacadas por peoes inimigos nao contam como mobilidade
+    let mobility_area = !(own | pawn_attacks(enemy_pawns, !color));
+
+    let mut score = Score::default();
+
+    for square in board.pieces(Piece::Knight) & own {
+        score += mobility(get_knight_moves(square) & mobility_area, KNIGHT_MOBILITY);
+
+        let defended = get_pawn_attacks(square, !color, own_pawns) != EMPTY;
+        let neighbours = get_adjacent_files(square.get_file());
+        let safe = enemy_pawns & neighbours & ranks_ahead(square, color) == EMPTY;
+        if (3..=5).contains(&relative_rank(square, color)) && defended && safe {
+            score += KNIGHT_OUTPOST;
+        }
+    }
+
+    let bishops = board.pieces(Piece::Bishop) & own;
+    for square in bishops {
+        score += mobility(get_bishop_moves(square, occupied) & mobility_area, BISHOP_MOBILITY);
+    }
+    if bishops.popcnt() >= 2 {
+        score += BISHOP_PAIR;
+    }
+
+    let seventh = get_rank(if color == Color::White { Rank::Seventh } else { Rank::Second });
+    let eighth = get_rank(if color == Color::White { Rank::Eighth } else { Rank::First });
+    for square in board.pieces(Piece::Rook) & own {
+        score += mobility(get_rook_moves(square, occupied) & mobility_area, ROOK_MOBILITY);
+
+        let file = get_file(square.get_file());
+        if own_pawns & file == EMPTY {
+            score += if enemy_pawns & file == EMPTY { ROOK_OPEN_FILE } else { ROOK_HALF_OPEN_FILE };
+        }
+
+        let enemy_king = BitBoard::from_square(board.king_square(!color));
+        let on_seventh = BitBoard::from_square(square) & seventh != EMPTY;
+        if on_seventh && (enemy_king & eighth != EMPTY || enemy_pawns & seventh != EMPTY) {
+            score += ROOK_ON_SEVENTH;
+        }
+    }
+
+    for square in board.pieces(Piece::Queen) & own {
+        let attacks = get_bishop_moves(square, occupied) | get_rook_moves(square, occupied);
+        score += mobility(attacks & mobility_area, QUEEN_MOBILITY);
+    }
+
+    score
+}
+
+fn mobility(squares: BitBoard, (weight, normal): (Score, i32)) -> Score {
+    weight * (squares.popcnt() as i32 - normal)
+}
+
+// todas as casas atacadas pelos peoes de uma cor
+fn pawn_attacks(pawns: BitBoard, color: Color) -> BitBoard {
+    pawns.fold(EMPTY, |attacks, square: Square| attacks | get_pawn_attacks(square, color, !EMPTY))
+}
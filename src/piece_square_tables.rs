@@ -1,6 +1,13 @@
-// values taken from: https://www.chessprogramming.org/Simplified_Evaluation_Function
+// middlegame values taken from: https://www.chessprogramming.org/Simplified_Evaluation_Function,
+// the endgame ones are written in the same spirit
+//
+// every table is written from white's point of view the way a board is printed:
+// the first row is rank 8 (index 0 = a8, index 7 = h8) and the last one is rank 1
+// (index 56 = a1, index 63 = h1). chess::Square counts the other way (a1 = 0), so the
+// evaluation looks white up at `square.to_index() ^ 56` and black, mirrored vertically,
+// at `square.to_index()`
 
-pub const PAWN_PST: [i32; 64] = [
+pub const MG_PAWN_PST: [i32; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
     50, 50, 50, 50, 50, 50, 50, 50,
     10, 10, 20, 30, 30, 20, 10, 10,
@@ -11,7 +18,7 @@ pub const PAWN_PST: [i32; 64] = [
      0,  0,  0,  0,  0,  0,  0,  0
 ];
 
-pub const KNIGHT_PST: [i32; 64] = [
+pub const MG_KNIGHT_PST: [i32; 64] = [
     -50,-40,-30,-30,-30,-30,-40,-50,
     -40,-20,  0,  0,  0,  0,-20,-40,
     -30,  0, 10, 15, 15, 10,  0,-30,
@@ -22,7 +29,7 @@ pub const KNIGHT_PST: [i32; 64] = [
     -50,-40,-30,-30,-30,-30,-40,-50,
 ];
 
-pub const BISHOP_PST: [i32; 64] = [
+pub const MG_BISHOP_PST: [i32; 64] = [
     -20,-10,-10,-10,-10,-10,-10,-20,
     -10,  0,  0,  0,  0,  0,  0,-10,
     -10,  0,  5, 10, 10,  5,  0,-10,
@@ -33,7 +40,7 @@ pub const BISHOP_PST: [i32; 64] = [
     -20,-10,-10,-10,-10,-10,-10,-20,
 ];
 
-pub const ROOK_PST: [i32; 64] = [
+pub const MG_ROOK_PST: [i32; 64] = [
     0,  0,  0,  0,  0,  0,  0,  0,
     5, 10, 10, 10, 10, 10, 10,  5,
    -5,  0,  0,  0,  0,  0,  0, -5,
@@ -44,7 +51,7 @@ pub const ROOK_PST: [i32; 64] = [
     0,  0,  0,  5,  5,  0,  0,  0
 ];
 
-pub const QUEEN_PST: [i32; 64] = [
+pub const MG_QUEEN_PST: [i32; 64] = [
     -20,-10,-10, -5, -5,-10,-10,-20,
     -10,  0,  0,  0,  0,  0,  0,-10,
     -10,  0,  5,  5,  5,  5,  0,-10,
@@ -66,6 +73,61 @@ pub const MG_KING_PST: [i32; 64] = [
      20, 30, 10,  0,  0, 10, 30, 20
 ];
 
+pub const EG_PAWN_PST: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    80, 80, 80, 80, 80, 80, 80, 80,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    30, 30, 30, 30, 30, 30, 30, 30,
+    15, 15, 15, 15, 15, 15, 15, 15,
+     5,  5,  5,  5,  5,  5,  5,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0
+];
+
+pub const EG_KNIGHT_PST: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,-10, -5, -5,-10,-20,-40,
+    -30,-10,  5, 10, 10,  5,-10,-30,
+    -30, -5, 10, 15, 15, 10, -5,-30,
+    -30, -5, 10, 15, 15, 10, -5,-30,
+    -30,-10,  5, 10, 10,  5,-10,-30,
+    -40,-20,-10, -5, -5,-10,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50
+];
+
+pub const EG_BISHOP_PST: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20
+];
+
+pub const EG_ROOK_PST: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    10, 10, 10, 10, 10, 10, 10, 10,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0,
+     0,  0,  0,  0,  0,  0,  0,  0
+];
+
+pub const EG_QUEEN_PST: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -10,  5, 10, 10, 10, 10,  5,-10,
+     -5,  5, 10, 15, 15, 10,  5, -5,
+     -5,  5, 10, 15, 15, 10,  5, -5,
+    -10,  5, 10, 10, 10, 10,  5,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20
+];
+
 pub const EG_KING_PST: [i32; 64] = [
     -50,-40,-30,-20,-20,-30,-40,-50,
     -30,-20,-10,  0,  0,-10,-20,-30,
@@ -75,4 +137,4 @@ pub const EG_KING_PST: [i32; 64] = [
     -30,-10, 20, 30, 30, 20,-10,-30,
     -30,-30,  0,  0,  0,  0,-30,-30,
     -50,-30,-30,-30,-30,-30,-30,-50
-];
\ No newline at end of file
+];
@@ -0,0 +1,146 @@
+use chess::{Color, Piece};
+use eframe::egui::{self, Pos2, Rect, Vec2};
+use super::ChessApp;
+use rust_chess_engine::pgn::PgnMove;
+use rust_chess_engine::{captured_piece, parse_pgn, FenPosition, PgnGame};
+
+// a loaded game, stepped through one ply at a time
+pub struct Replay {
+    pub game: PgnGame,
+    // positions[i] is the position after i plies
+    positions: Vec<FenPosition>,
+    // what each move took, and whose piece it was
+    captures: Vec<Option<(Color, Piece)>>,
+    pub ply: usize,
+}
+
+impl Replay {
+    pub fn new(game: PgnGame) -> Self {
+        let mut positions = vec![game.start];
+        let mut captures = Vec::new();
+
+        for pgn_move in &game.moves {
+            let mut position = *positions.last().unwrap();
+
+            let victim_color = !position.board.side_to_move();
+            captures.push(captured_piece(&position.board, pgn_move.mv).map(|piece| (victim_color, piece)));
+
+            position.make_move(pgn_move.mv);
+            positions.push(position);
+        }
+
+        Self { game, positions, captures, ply: 0 }
+    }
+
+    pub fn position(&self) -> &FenPosition {
+        &self.positions[self.ply]
+    }
+
+    pub fn last_ply(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    // the move that led to the position on screen
+    pub fn current_move(&self) -> Option<&PgnMove> {
+        self.ply.checked_sub(1).map(|index| &self.game.moves[index])
+    }
+
+    pub fn first(&mut self) {
+        self.ply = 0;
+    }
+
+    pub fn previous(&mut self) {
+        self.ply = self.ply.saturating_sub(1);
+    }
+
+    pub fn next(&mut self) {
+        self.ply = (self.ply + 1).min(self.last_ply());
+    }
+
+    pub fn last(&mut self) {
+        self.ply = self.last_ply();
+    }
+
+    // the pieces of `color` taken up to the position on screen
+    pub fn slain_pieces(&self, color: Color) -> Vec<Piece> {
+        self.captures[..self.ply]
+            .iter()
+            .flatten()
+            .filter(|(victim_color, _)| *victim_color == color)
+            .map(|(_, piece)| *piece)
+            .collect()
+    }
+}
+
+impl ChessApp {
+    // the game being played waits in the background while a replay is open
+    pub fn load_pgn(&mut self) {
+        let text = match std::fs::read_to_string(&self.pgn_path) {
+            Ok(text) => text,
+            Err(error) => {
+                self.pgn_status = Some(format!("could not read {}: {}", self.pgn_path, error));
+                return;
+            },
+        };
+
+        match parse_pgn(&text) {
+            Ok(game) => {
+                self.pgn_status = Some(format!("loaded {} plies", game.moves.len()));
+                self.replay = Some(Replay::new(game));
+                if let Some(clocks) = &mut self.clocks {
+                    clocks.stop();
+                }
+                // with its clock stopped the engine must not keep thinking: it settles on a move
+                // now, which is played once the replay is closed
+                self.move_now();
+            },
+            Err(error) => self.pgn_status = Some(error.to_string()),
+        }
+    }
+
+    // first/previous/next/last buttons under the board, also on home/left/right/end
+    pub fn replay_controls(&mut self, ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8]) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+
+        let board_left = tiles[7][0].min.x;
+        let board_width = tiles[7][7].max.x - board_left;
+        let square_size = tiles[7][0].height();
+        let button_size = Vec2::new(40.0, 24.0);
+        let spacing = 8.0;
+
+        // below the row of captured pieces
+        let top = tiles[7][0].max.y + square_size / 2.0 + spacing;
+        let mut left = board_left + (board_width - 4.0 * button_size.x - 3.0 * spacing) / 2.0;
+        // the arrow keys belong to the text fields while one of them is being edited
+        let keys_enabled = !ui.ctx().wants_keyboard_input();
+
+        for (label, key) in [("|<", egui::Key::Home), ("<", egui::Key::ArrowLeft), (">", egui::Key::ArrowRight), (">|", egui::Key::End)] {
+            let rect = Rect::from_min_size(Pos2::new(left, top), button_size);
+            let clicked = ui.put(rect, egui::Button::new(label)).clicked();
+
+            if clicked || (keys_enabled && ui.input(|i| i.key_pressed(key))) {
+                match key {
+                    egui::Key::Home => replay.first(),
+                    egui::Key::ArrowLeft => replay.previous(),
+                    egui::Key::ArrowRight => replay.next(),
+                    _ => replay.last(),
+                }
+            }
+
+            left += button_size.x + spacing;
+        }
+    }
+
+    pub fn close_replay(&mut self) {
+        self.replay = None;
+        self.pgn_status = None;
+
+        if let Some(clocks) = &mut self.clocks {
+            if !self.game_is_over {
+                clocks.start(self.position.board.side_to_move());
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use chess::ChessMove;
+
+// tipo de limite guardado junto com a pontuacao:
+// Exact eh o valor real, Lower/Upper vieram de um corte alpha-beta
+#[derive(PartialEq, Clone, Copy)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Copy)]
+pub struct TtEntry {
+    pub key: u64,
+    pub depth: u32,
+    pub bound: Bound,
+    pub score: i32,
+    pub best_move: Option<ChessMove>,
+}
+
+// tabela de tamanho fixo indexada pelo hash zobrist do tabuleiro (Board::get_hash)
+pub struct TranspositionTable {
+    entries: Vec<Option<TtEntry>>,
+    size_mb: usize,
+    pub hits: u64,
+    pub overwrites: u64,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let entry_count = (size_mb.max(1) * 1024 * 1024) / std::mem::size_of::<Option<TtEntry>>();
+
+        Self {
+            entries: vec![None; entry_count],
+            size_mb,
+            hits: 0,
+            overwrites: 0,
+        }
+    }
+
+    pub fn size_mb(&self) -> usize {
+        self.size_mb
+    }
+
+    pub fn resize(&mut self, size_mb: usize) {
+        *self = Self::new(size_mb);
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.hits = 0;
+        self.overwrites = 0;
+    }
+
+    pub fn probe(&mut self, key: u64) -> Option<TtEntry> {
+        let index = self.index(key);
+
+        match self.entries[index] {
+            Some(entry) if entry.key == key => {
+                self.hits += 1;
+                Some(entry)
+            },
+            _ => None,
+        }
+    }
+
+    // o melhor movimento guardado para a posicao, sem contar como acerto
+    pub fn best_move(&self, key: u64) -> Option<ChessMove> {
+        match self.entries[self.index(key)] {
+            Some(entry) if entry.key == key => entry.best_move,
+            _ => None,
+        }
+    }
+
+    // outra posicao no mesmo indice sempre eh substituida; a mesma posicao
+    // so eh substituida por uma busca pelo menos tao profunda quanto a anterior
+    pub fn store(&mut self, entry: TtEntry) {
+        let index = self.index(entry.key);
+
+        match self.entries[index] {
+            Some(old) if old.key == entry.key => {
+                if entry.depth >= old.depth || entry.bound == Bound::Exact {
+                    self.entries[index] = Some(entry);
+                }
+            },
+            Some(_) => {
+                self.overwrites += 1;
+                self.entries[index] = Some(entry);
+            },
+            None => self.entries[index] = Some(entry),
+        }
+    }
+
+    fn index(&self, key: u64) -> usize {
+        (key % self.entries.len() as u64) as usize
+    }
+}
@@ -0,0 +1,234 @@
+use std::io::{self, BufRead};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use chess::{Board, ChessMove, Color, MoveGen};
+use crate::best_move::{mate_distance, SearchLimits, SearchOptions, SearchResult, SearchStats, MAX_DEPTH};
+use crate::engine::{Engine, DEFAULT_HASH_MB};
+
+const DEFAULT_DEPTH: u32 = 5;
+
+// state kept between uci commands; the search itself runs on its own thread
+// so "stop" and "isready" are answered while it thinks
+struct UciEngine {
+    engine: Arc<Mutex<Engine>>,
+    depth: u32,
+    stop: Arc<AtomicBool>,
+    search_thread: Option<JoinHandle<()>>,
+}
+
+// speaks the universal chess interface over stdin/stdout (the `uci` binary); the gui binary
+// can't, being a windows-subsystem program without a console on windows
+pub fn run() {
+    let mut engine = UciEngine {
+        engine: Arc::new(Mutex::new(Engine::new(DEFAULT_HASH_MB))),
+        depth: DEFAULT_DEPTH,
+        stop: Arc::new(AtomicBool::new(false)),
+        search_thread: None,
+    };
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+
+        match tokens.first() {
+            Some(&"uci") => {
+                println!("id name rust-chess-engine");
+                println!("id author pedromonteir1111");
+                println!("option name Hash type spin default {} min 1 max 1024", DEFAULT_HASH_MB);
+                println!("option name Depth type spin default {} min 1 max {}", DEFAULT_DEPTH, MAX_DEPTH);
+                println!("option name Pruning type check default true");
+                println!("option name Contempt type spin default 0 min -100 max 100");
+                println!("uciok");
+            },
+            Some(&"isready") => println!("readyok"),
+            Some(&"ucinewgame") => {
+                engine.stop_search();
+                engine.engine.lock().unwrap().new_game();
+            },
+            Some(&"setoption") => engine.set_option(&tokens[1..]),
+            Some(&"position") => engine.set_position(&tokens[1..]),
+            Some(&"go") => engine.go(&tokens[1..]),
+            Some(&"stop") => engine.stop_search(),
+            Some(&"quit") => {
+                engine.stop_search();
+                break;
+            },
+            _ => (),
+        }
+    }
+}
+
+impl UciEngine {
+    // setoption name <name> value <value>; names may contain spaces
+    fn set_option(&mut self, tokens: &[&str]) {
+        self.stop_search();
+        let mut engine = self.engine.lock().unwrap();
+
+        let value_at = tokens.iter().position(|token| *token == "value").unwrap_or(tokens.len());
+        let name = tokens.get(1..value_at).unwrap_or_default().join(" ").to_lowercase();
+        let value = tokens.get(value_at + 1..).unwrap_or_default().join(" ");
+
+        match name.as_str() {
+            "hash" => match value.parse::<usize>() {
+                Ok(hash_mb) => engine.set_hash_size(hash_mb.clamp(1, 1024)),
+                Err(_) => println!("info string invalid hash size: {}", value),
+            },
+            "depth" => match value.parse::<u32>() {
+                Ok(depth) => self.depth = depth.clamp(1, MAX_DEPTH),
+                Err(_) => println!("info string invalid depth: {}", value),
+            },
+            "pruning" => {
+                let options = SearchOptions { pruning: value.eq_ignore_ascii_case("true"), ..engine.options() };
+                engine.set_options(options);
+            },
+            "contempt" => match value.parse::<i32>() {
+                Ok(contempt) => {
+                    let options = SearchOptions { contempt: contempt.clamp(-100, 100), ..engine.options() };
+                    engine.set_options(options);
+                },
+                Err(_) => println!("info string invalid contempt: {}", value),
+            },
+            _ => println!("info string unknown option: {}", name),
+        }
+    }
+
+    // position [startpos | fen <fen>] [moves <move> ...]
+    fn set_position(&mut self, tokens: &[&str]) {
+        self.stop_search();
+        let mut engine = self.engine.lock().unwrap();
+
+        let moves_at = tokens.iter().position(|token| *token == "moves").unwrap_or(tokens.len());
+
+        match tokens.first() {
+            Some(&"startpos") => engine.set_position(Board::default()),
+            Some(&"fen") => {
+                if let Err(error) = engine.set_fen(&tokens[1..moves_at].join(" ")) {
+                    println!("info string invalid fen: {}", error);
+                    return;
+                }
+            },
+            _ => return,
+        }
+
+        for text in tokens.iter().skip(moves_at + 1) {
+            match ChessMove::from_str(text) {
+                Ok(mv) if engine.make_move(mv) => (),
+                _ => {
+                    println!("info string illegal move: {}", text);
+                    return;
+                },
+            }
+        }
+    }
+
+    // go [depth N] [movetime ms] [wtime ms] [btime ms] [winc ms] [binc ms] [movestogo N] [nodes N] [infinite]
+    fn go(&mut self, tokens: &[&str]) {
+        self.stop_search();
+
+        let value = |name: &str| -> Option<u64> {
+            let at = tokens.iter().position(|token| *token == name)?;
+            tokens.get(at + 1)?.parse().ok()
+        };
+
+        let board = *self.engine.lock().unwrap().position();
+
+        // checkmate or stalemate: there is nothing to search, not even with "go infinite"
+        if MoveGen::new_legal(&board).len() == 0 {
+            println!("bestmove 0000");
+            return;
+        }
+
+        let infinite = tokens.contains(&"infinite");
+        let (time_left, increment) = match board.side_to_move() {
+            Color::White => (value("wtime"), value("winc")),
+            Color::Black => (value("btime"), value("binc")),
+        };
+
+        let mut limits = SearchLimits::fixed_depth(self.depth);
+
+        if let Some(time_left) = time_left {
+            limits = SearchLimits::from_clock(
+                Duration::from_millis(time_left),
+                Duration::from_millis(increment.unwrap_or(0)),
+                value("movestogo").map(|moves| moves as u32),
+            );
+        }
+        if let Some(move_time) = value("movetime") {
+            limits = SearchLimits::fixed_time(Duration::from_millis(move_time));
+        }
+        if let Some(nodes) = value("nodes") {
+            limits.depth = MAX_DEPTH;
+            limits.nodes = Some(nodes as i64);
+        }
+        if infinite {
+            limits = SearchLimits { depth: MAX_DEPTH, move_time: None, nodes: None };
+        }
+        if let Some(depth) = value("depth") {
+            limits.depth = (depth as u32).clamp(1, MAX_DEPTH);
+        }
+
+        let engine = Arc::clone(&self.engine);
+
+        self.stop = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&self.stop);
+
+        self.search_thread = Some(thread::spawn(move || {
+            let result = engine.lock().unwrap().search_with_info(
+                &limits,
+                &stop,
+                &mut |result, stats| print_info(&board, result, stats),
+            );
+
+            // with "go infinite" the best move may only be sent after "stop"
+            while infinite && !stop.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            // a search stopped before its first iteration still answers with a legal move
+            match result.best_move.or_else(|| MoveGen::new_legal(&board).next()) {
+                Some(mv) => println!("bestmove {}", mv),
+                None => println!("bestmove 0000"),
+            }
+        }));
+    }
+
+    fn stop_search(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+
+        if let Some(search_thread) = self.search_thread.take() {
+            let _ = search_thread.join();
+        }
+    }
+}
+
+fn print_info(board: &Board, result: &SearchResult, stats: &SearchStats) {
+    // uci scores are from the side to move, the engine's are from white
+    let score = if board.side_to_move() == Color::White { result.score } else { -result.score };
+    let score = match mate_distance(score) {
+        Some(moves) => format!("mate {}", moves),
+        None => format!("cp {}", score),
+    };
+
+    let nodes = stats.count + stats.qnodes;
+    let millis = stats.time_elapsed.as_millis().max(1);
+    let mut line = format!(
+        "info depth {} score {} nodes {} nps {} time {}",
+        stats.depth,
+        score,
+        nodes,
+        nodes as u128 * 1000 / millis,
+        stats.time_elapsed.as_millis()
+    );
+
+    // an empty pv is left out rather than sent as a bare "pv"
+    if !result.pv.is_empty() {
+        let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_string()).collect();
+        line += &format!(" pv {}", pv.join(" "));
+    }
+
+    println!("{}", line);
+}
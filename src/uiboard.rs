@@ -1,6 +1,6 @@
-use crate::{action_manager::TurnStates, best_move};
+use rust_chess_engine::evaluation::{evaluate_board, evaluation_trace, MAX_PHASE};
 use super::ChessApp;
-use chess::{BitBoard, Color, Piece};
+use chess::{BitBoard, Board, BoardStatus, Color, Piece};
 use eframe::egui::{self, Pos2, Rect, Vec2, Color32};
 
 #[derive(PartialEq, Copy, Clone)]
@@ -22,12 +22,13 @@ enum PiecesAndColors {
 // bloco de implementacao para mostrar o tabuleiro
 
 impl ChessApp {
+    #[allow(clippy::needless_range_loop)]
     pub fn display_board (
         &mut self,
         ui: &mut egui::Ui,
         ctx: &egui::Context,
         board_image: egui::Image<'_>,
-        piece_images: &Vec<egui::Image<'_>>,
+        piece_images: &[egui::Image<'_>],
         top_panel_height: f32,
     ) -> [[Rect; 8]; 8]  {
         let panel_size = ui.available_size();
@@ -52,6 +53,13 @@ impl ChessApp {
             }
         }        
 
+        // the coordinates are painted into the image, so they turn with the board
+        let board_image = if self.white_at_bottom() {
+            board_image
+        } else {
+            board_image.rotate(std::f32::consts::PI, Vec2::splat(0.5))
+        };
+
         ui.put(board_rect, board_image);
 
         self.display_captured(ui, board_upperleft, Pos2::new(board_rect.min.x, board_rect.max.y), piece_images, square_size);
@@ -64,39 +72,35 @@ impl ChessApp {
     pub fn display_pieces(
         &self, 
         ui: &mut egui::Ui,
-        piece_images: &Vec<egui::Image<'_>>,
+        piece_images: &[egui::Image<'_>],
         tiles: &[[Rect; 8]; 8]) {
         
+        let board = self.displayed_board();
+
         let bitboards: [(BitBoard, PiecesAndColors) ; 12] = [
-            (self.board.pieces(Piece::Pawn).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhitePawn),
-            (self.board.pieces(Piece::Bishop).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhiteBishop),
-            (self.board.pieces(Piece::Knight).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhiteKnight),
-            (self.board.pieces(Piece::Rook).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhiteRook),
-            (self.board.pieces(Piece::Queen).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhiteQueen),
-            (self.board.pieces(Piece::King).clone() & self.board.color_combined(Color::White), PiecesAndColors::WhiteKing),
-            (self.board.pieces(Piece::Pawn).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackPawn),
-            (self.board.pieces(Piece::Bishop).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackBishop),
-            (self.board.pieces(Piece::Knight).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackKnight),
-            (self.board.pieces(Piece::Rook).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackRook),
-            (self.board.pieces(Piece::Queen).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackQueen),
-            (self.board.pieces(Piece::King).clone() & self.board.color_combined(Color::Black), PiecesAndColors::BlackKing),
+            (*board.pieces(Piece::Pawn) & board.color_combined(Color::White), PiecesAndColors::WhitePawn),
+            (*board.pieces(Piece::Bishop) & board.color_combined(Color::White), PiecesAndColors::WhiteBishop),
+            (*board.pieces(Piece::Knight) & board.color_combined(Color::White), PiecesAndColors::WhiteKnight),
+            (*board.pieces(Piece::Rook) & board.color_combined(Color::White), PiecesAndColors::WhiteRook),
+            (*board.pieces(Piece::Queen) & board.color_combined(Color::White), PiecesAndColors::WhiteQueen),
+            (*board.pieces(Piece::King) & board.color_combined(Color::White), PiecesAndColors::WhiteKing),
+            (*board.pieces(Piece::Pawn) & board.color_combined(Color::Black), PiecesAndColors::BlackPawn),
+            (*board.pieces(Piece::Bishop) & board.color_combined(Color::Black), PiecesAndColors::BlackBishop),
+            (*board.pieces(Piece::Knight) & board.color_combined(Color::Black), PiecesAndColors::BlackKnight),
+            (*board.pieces(Piece::Rook) & board.color_combined(Color::Black), PiecesAndColors::BlackRook),
+            (*board.pieces(Piece::Queen) & board.color_combined(Color::Black), PiecesAndColors::BlackQueen),
+            (*board.pieces(Piece::King) & board.color_combined(Color::Black), PiecesAndColors::BlackKing),
         ];
 
         for (bitboard, bb_type) in bitboards {
-            for rank in (0..8).rev() {
-                for file in 0..8 {
-                    let square_index = (7 - rank) * 8 + file;
-                    let bit = (bitboard.0 >> square_index) & 1;
-                    
-                    if bit == 1 {
-                        ui.put(tiles[rank][file], piece_images[piece_to_index(&bb_type)].clone());
-                    } 
-                }
-            }           
+            for square in bitboard {
+                let (row, col) = square_to_row_col(square, self.white_at_bottom());
+                ui.put(tiles[row][col], piece_images[piece_to_index(&bb_type)].clone());
+            }
         }
     }
 
-    fn display_captured(&self, ui: &mut egui::Ui, board_upperleft: Pos2, board_bottomleft: Pos2, piece_images: &Vec<egui::Image<'_>>, square_size: f32) {
+    fn display_captured(&self, ui: &mut egui::Ui, board_upperleft: Pos2, board_bottomleft: Pos2, piece_images: &[egui::Image<'_>], square_size: f32) {
         
         fn get_priority(piece: &PiecesAndColors) -> usize {
             match piece {
@@ -115,7 +119,12 @@ impl ChessApp {
         let mut top_pieces: Vec<PiecesAndColors> = Vec::new();
         let mut bottom_pieces: Vec<PiecesAndColors> = Vec::new();
 
-        for piece in self.white_slain_pieces.clone() {
+        let (white_slain_pieces, black_slain_pieces) = match &self.replay {
+            Some(replay) => (replay.slain_pieces(Color::White), replay.slain_pieces(Color::Black)),
+            None => (self.white_slain_pieces.clone(), self.black_slain_pieces.clone()),
+        };
+
+        for piece in white_slain_pieces {
             
             let piece_and_color = match piece {
                 Piece::Pawn => PiecesAndColors::WhitePawn,
@@ -142,7 +151,7 @@ impl ChessApp {
             }
         }
 
-        for piece in self.black_slain_pieces.clone() {
+        for piece in black_slain_pieces {
             let piece_and_color = match piece {
                 Piece::Pawn => PiecesAndColors::BlackPawn,
                 Piece::Knight => PiecesAndColors::BlackKnight,
@@ -168,6 +177,11 @@ impl ChessApp {
             }
         }
 
+        // each side's losses sit on the opposite edge of the board
+        if !self.white_at_bottom() {
+            std::mem::swap(&mut top_pieces, &mut bottom_pieces);
+        }
+
         for (i, piece) in top_pieces.into_iter().enumerate() {
             ui.put(
                 Rect::from_min_size(
@@ -204,19 +218,34 @@ impl ChessApp {
 
     fn draw_evaluation_bar(&self, ctx: &egui::Context, ui: &mut egui::Ui, position: Pos2, size: Vec2) {
        
-        let evaluation = best_move::evaluate_board(&self.board);
+        let board = self.displayed_board();
+        let evaluation = evaluate_board(board);
 
         let max_eval = 3000;
         let min_eval = -3000;
-        let eval_clamped = if !matches!(self.turn_state, TurnStates::Checkmate) {evaluation.clamp(min_eval, max_eval) as f32} else {max_eval as f32};
+        // the bar fills up for whoever delivered the mate
+        let eval_clamped = if board.status() != BoardStatus::Checkmate {
+            evaluation.clamp(min_eval, max_eval) as f32
+        } else if board.side_to_move() == Color::White {
+            min_eval as f32
+        } else {
+            max_eval as f32
+        };
         let eval_percent = (eval_clamped - min_eval as f32) / (max_eval - min_eval) as f32;
         let rect = Rect::from_min_size(position, size);
         let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("evaluation_bar")));
 
-        let mid_y = rect.bottom() - (rect.height() * eval_percent as f32);
+        // white's share grows from white's edge of the board
+        let white_rect = if self.white_at_bottom() {
+            let mid_y = rect.bottom() - (rect.height() * eval_percent);
+            Rect::from_min_max(Pos2::new(rect.left(), mid_y), rect.max)
+        } else {
+            let mid_y = rect.top() + (rect.height() * eval_percent);
+            Rect::from_min_max(rect.min, Pos2::new(rect.right(), mid_y))
+        };
     
         painter.rect_filled(rect, 0.0, Color32::BLACK);
-        painter.rect_filled(Rect::from_min_max(Pos2::new(rect.left(), mid_y), rect.max), 0.0, Color32::WHITE);
+        painter.rect_filled(white_rect, 0.0, Color32::WHITE);
     
         let response = ui.interact(rect, egui::Id::new("evaluation_bar_interaction"), egui::Sense::hover());
 
@@ -224,19 +253,53 @@ impl ChessApp {
         if response.hovered() {
             egui::show_tooltip(ctx, response.id, |ui| {
                 ui.label(format!("Evaluation: {:.2}", evaluation));
+                // how much of the middlegame values went into the number above
+                let trace = evaluation_trace(board);
+                ui.label(format!("Phase: {}% middlegame", trace.phase * 100 / MAX_PHASE));
+                ui.separator();
+
+                // every term already tapered, in centipawns; the columns may be off by
+                // one from the total because of rounding
+                egui::Grid::new("evaluation_trace").striped(true).show(ui, |ui| {
+                    for header in ["Term", "White", "Black", "Total"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+
+                    for term in &trace.terms {
+                        ui.label(term.name);
+                        ui.label(term.white.taper(trace.phase).to_string());
+                        ui.label(term.black.taper(trace.phase).to_string());
+                        ui.label(term.score().taper(trace.phase).to_string());
+                        ui.end_row();
+                    }
+                });
             });
         }
     }
 
-    pub fn display_possible_actions(&mut self, ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8], icons: &Vec<egui::Image<'_>>) {
+    // the human plays up the board, unless the board is flipped
+    pub fn white_at_bottom(&self) -> bool {
+        (self.human_color == Color::White) != self.flip_board
+    }
+
+    // the replay's position while one is open, the game's otherwise
+    fn displayed_board(&self) -> &Board {
+        match &self.replay {
+            Some(replay) => &replay.position().board,
+            None => &self.position.board,
+        }
+    }
+
+    pub fn display_possible_actions(&mut self, ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8], icons: &[egui::Image<'_>]) {
         if !self.legal_moves_from_source.is_empty() {
             for mv in self.legal_moves_from_source.clone() {
                 let dest_square = mv.get_dest();
-                let (row, col) = square_to_row_col(dest_square);
+                let (row, col) = square_to_row_col(dest_square, self.white_at_bottom());
                 
                 let mut index: usize = 0;
 
-                if self.board.piece_on(dest_square).is_some() {
+                if self.position.board.piece_on(dest_square).is_some() {
                     index = 1;
                 }
 
@@ -247,7 +310,9 @@ impl ChessApp {
 
 }
 
-pub fn detect_clicked_square(ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8]) -> Option<(usize, usize)> {
+// the (rank, file) of the clicked square
+#[allow(clippy::needless_range_loop)]
+pub fn detect_clicked_square(ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8], white_at_bottom: bool) -> Option<(usize, usize)> {
 
     let mouse_pos = match ui.input(|i| i.pointer.interact_pos()) {
         Some(pos) => pos,
@@ -260,7 +325,7 @@ pub fn detect_clicked_square(ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8]) -> Optio
                 let rect = tiles[row][col];
                 
                 if rect.contains(mouse_pos) {
-                    return Some((7 - row, col));
+                    return Some(if white_at_bottom { (7 - row, col) } else { (row, 7 - col) });
                 }
             }
         }
@@ -268,6 +333,26 @@ pub fn detect_clicked_square(ui: &mut egui::Ui, tiles: &[[Rect; 8]; 8]) -> Optio
     None
 }
 
+// where a piece's sprite sits in the image list
+pub fn piece_image_index(piece: Piece, color: Color) -> usize {
+    let piece_and_color = match (piece, color) {
+        (Piece::Pawn, Color::White) => PiecesAndColors::WhitePawn,
+        (Piece::Knight, Color::White) => PiecesAndColors::WhiteKnight,
+        (Piece::Bishop, Color::White) => PiecesAndColors::WhiteBishop,
+        (Piece::Rook, Color::White) => PiecesAndColors::WhiteRook,
+        (Piece::Queen, Color::White) => PiecesAndColors::WhiteQueen,
+        (Piece::King, Color::White) => PiecesAndColors::WhiteKing,
+        (Piece::Pawn, Color::Black) => PiecesAndColors::BlackPawn,
+        (Piece::Knight, Color::Black) => PiecesAndColors::BlackKnight,
+        (Piece::Bishop, Color::Black) => PiecesAndColors::BlackBishop,
+        (Piece::Rook, Color::Black) => PiecesAndColors::BlackRook,
+        (Piece::Queen, Color::Black) => PiecesAndColors::BlackQueen,
+        (Piece::King, Color::Black) => PiecesAndColors::BlackKing,
+    };
+
+    piece_to_index(&piece_and_color)
+}
+
 fn piece_to_index(bb_type: &PiecesAndColors) -> usize {
     match bb_type {
         PiecesAndColors::WhitePawn => 0,
@@ -285,9 +370,14 @@ fn piece_to_index(bb_type: &PiecesAndColors) -> usize {
     }
 }
 
-fn square_to_row_col(square: chess::Square) -> (usize, usize) {
+// the square's place on screen; flipping the board turns it around by 180 degrees
+pub fn square_to_row_col(square: chess::Square, white_at_bottom: bool) -> (usize, usize) {
     let row = square.get_rank().to_index();
     let col = square.get_file().to_index();
-    (7 - row, col)
+    if white_at_bottom {
+        (7 - row, col)
+    } else {
+        (row, 7 - col)
+    }
 }
 
@@ -0,0 +1,66 @@
+use chess::{ChessMove, Square};
+use rust_chess_engine::{draw_reason, parse_fen, DrawReason, FenPosition};
+
+// plays the moves and keeps the hash of every position, the current one included
+fn play(mut position: FenPosition, moves: &[(Square, Square)]) -> (FenPosition, Vec<u64>) {
+    let mut history = vec![position.board.get_hash()];
+
+    for (source, dest) in moves {
+        position.make_move(ChessMove::new(*source, *dest, None));
+        history.push(position.board.get_hash());
+    }
+
+    (position, history)
+}
+
+fn draw_in(fen: &str) -> Option<DrawReason> {
+    let position = parse_fen(fen).unwrap();
+    draw_reason(&position, &[position.board.get_hash()])
+}
+
+const KNIGHT_DANCE: [(Square, Square); 4] =
+    [(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::F3, Square::G1), (Square::F6, Square::G8)];
+
+#[test]
+fn threefold_repetition() {
+    let (position, history) = play(FenPosition::default(), &KNIGHT_DANCE);
+    // the starting position has been seen twice
+    assert_eq!(draw_reason(&position, &history), None);
+
+    let (position, history) = play(FenPosition::default(), &[KNIGHT_DANCE, KNIGHT_DANCE].concat());
+    assert_eq!(draw_reason(&position, &history), Some(DrawReason::Repetition));
+}
+
+#[test]
+fn fifty_move_rule() {
+    let start = parse_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 99 80").unwrap();
+    assert_eq!(draw_reason(&start, &[start.board.get_hash()]), None);
+
+    let (position, history) = play(start, &[(Square::A1, Square::A2)]);
+    assert_eq!(draw_reason(&position, &history), Some(DrawReason::FiftyMoves));
+
+    // a pawn move starts the count again
+    let (position, history) = play(start, &[(Square::E2, Square::E4)]);
+    assert_eq!(draw_reason(&position, &history), None);
+}
+
+#[test]
+fn insufficient_material() {
+    assert_eq!(draw_in("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), Some(DrawReason::InsufficientMaterial));
+    assert_eq!(draw_in("4k3/8/8/8/8/8/8/2B1K3 w - - 0 1"), Some(DrawReason::InsufficientMaterial));
+    assert_eq!(draw_in("4k3/8/8/8/8/8/8/1N2K3 w - - 0 1"), Some(DrawReason::InsufficientMaterial));
+    assert_eq!(draw_in("1n2k3/8/8/8/8/8/8/4K3 w - - 0 1"), Some(DrawReason::InsufficientMaterial));
+
+    // anything more can still mate
+    assert_eq!(draw_in("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+    assert_eq!(draw_in("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), None);
+    assert_eq!(draw_in("4k3/8/8/8/8/8/8/1NN1K3 w - - 0 1"), None);
+    assert_eq!(draw_in("1n2k3/8/8/8/8/8/8/2B1K3 w - - 0 1"), None);
+}
+
+#[test]
+fn stalemate() {
+    assert_eq!(draw_in("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1"), Some(DrawReason::Stalemate));
+    // checkmate is not a draw of any kind
+    assert_eq!(draw_in("6Qk/8/6K1/8/8/8/8/8 b - - 0 1"), None);
+}
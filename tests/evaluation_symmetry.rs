@@ -0,0 +1,129 @@
+use std::str::FromStr;
+
+use chess::{Board, MoveGen};
+use rust_chess_engine::evaluation::{evaluate_board, evaluation_trace, Score};
+use rust_chess_engine::piece_square_tables::{EG_KNIGHT_PST, EG_PAWN_PST, MG_KNIGHT_PST, MG_PAWN_PST};
+
+const POSITIONS: [&str; 10] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "rnbqkb1r/pp1p1ppp/5n2/2pPp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 4",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "6k1/1R3ppp/8/8/8/8/5PPP/6K1 b - - 0 1",
+    "6k1/5ppp/8/3N4/2P1p3/8/5PPP/6K1 w - - 0 1",
+    "2kr3r/ppp2ppp/2n5/3q4/3P4/2PB1N2/P4PPP/R2Q1RK1 b - - 0 14",
+    "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 99 50",
+];
+
+// the same position with the board turned upside down and the colors swapped
+fn mirror_fen(fen: &str) -> String {
+    let fields: Vec<&str> = fen.split_whitespace().collect();
+
+    let placement: Vec<String> = fields[0].split('/').rev().map(swap_case).collect();
+    let side = if fields[1] == "w" { "b" } else { "w" };
+
+    let mirrored_rights = swap_case(fields[2]);
+    let castling: String = "KQkq".chars().filter(|right| mirrored_rights.contains(*right)).collect();
+    let castling = if castling.is_empty() { "-".to_string() } else { castling };
+
+    let en_passant = match fields[3] {
+        "-" => "-".to_string(),
+        square => {
+            let rank = if square.ends_with('3') { '6' } else { '3' };
+            format!("{}{}", &square[..1], rank)
+        },
+    };
+
+    format!("{} {} {} {} {}", placement.join("/"), side, castling, en_passant, fields[4..].join(" "))
+}
+
+fn swap_case(text: &str) -> String {
+    text.chars()
+        .map(|c| if c.is_ascii_uppercase() { c.to_ascii_lowercase() } else { c.to_ascii_uppercase() })
+        .collect()
+}
+
+fn mirror(board: &Board) -> Board {
+    Board::from_str(&mirror_fen(&board.to_string())).unwrap()
+}
+
+// every test position and everything reachable from it in two plies
+fn positions() -> Vec<Board> {
+    let mut boards = Vec::new();
+
+    for fen in POSITIONS {
+        let board = Board::from_str(fen).unwrap();
+        boards.push(board);
+
+        for first in MoveGen::new_legal(&board) {
+            let after_first = board.make_move_new(first);
+            boards.push(after_first);
+
+            for second in MoveGen::new_legal(&after_first) {
+                boards.push(after_first.make_move_new(second));
+            }
+        }
+    }
+
+    boards
+}
+
+#[test]
+fn mirror_fen_round_trips() {
+    for fen in POSITIONS {
+        assert_eq!(mirror_fen(&mirror_fen(fen)), fen);
+    }
+}
+
+#[test]
+fn mirrored_positions_evaluate_to_negated_scores() {
+    for board in positions() {
+        let mirrored = mirror(&board);
+        assert_eq!(evaluate_board(&mirrored), -evaluate_board(&board), "{} vs {}", board, mirrored);
+    }
+}
+
+// a failing symmetry test says something is off; this one says which term
+#[test]
+fn mirrored_positions_swap_every_term() {
+    for fen in POSITIONS {
+        let board = Board::from_str(fen).unwrap();
+        let trace = evaluation_trace(&board);
+        let mirrored_trace = evaluation_trace(&mirror(&board));
+
+        assert_eq!(trace.phase, mirrored_trace.phase);
+        for (term, mirrored_term) in trace.terms.iter().zip(&mirrored_trace.terms) {
+            assert_eq!(term.white, mirrored_term.black, "{} in {}", term.name, fen);
+            assert_eq!(term.black, mirrored_term.white, "{} in {}", term.name, fen);
+        }
+        assert_eq!(trace.total(), evaluate_board(&board), "{}", fen);
+    }
+}
+
+// symmetry alone would also hold with both colors reading the tables upside down
+#[test]
+fn piece_square_tables_are_read_from_whites_side() {
+    let pst = |fen: &str, name: &str| {
+        let trace = evaluation_trace(&Board::from_str(fen).unwrap());
+        let term = trace.terms.into_iter().find(|term| term.name == name).unwrap();
+        (term.white, term.black)
+    };
+
+    // g1 is in the last row, second column from the right; e7 in the second row
+    let (white, _) = pst("4k3/8/8/8/8/8/8/4K1N1 w - - 0 1", "Knight PST");
+    assert_eq!(white, Score::new(MG_KNIGHT_PST[62], EG_KNIGHT_PST[62]));
+
+    let (white, _) = pst("4k3/4P3/8/8/8/8/8/4K3 w - - 0 1", "Pawn PST");
+    assert_eq!(white, Score::new(MG_PAWN_PST[12], EG_PAWN_PST[12]));
+
+    // black's pawn on e2 is as far advanced as white's on e7
+    let (_, black) = pst("4k3/8/8/8/8/8/4p3/4K3 w - - 0 1", "Pawn PST");
+    assert_eq!(black, Score::new(MG_PAWN_PST[12], EG_PAWN_PST[12]));
+
+    // and a1 for white is a8 for black: the same corner of the table
+    let (white, black) = pst("n3k3/8/8/8/8/8/8/N3K3 w - - 0 1", "Knight PST");
+    assert_eq!(white, Score::new(MG_KNIGHT_PST[56], EG_KNIGHT_PST[56]));
+    assert_eq!(white, black);
+}
@@ -0,0 +1,103 @@
+use chess::{Board, ChessMove, Color, Piece, Square};
+use rust_chess_engine::{parse_fen, to_fen, FenError};
+
+fn position_error(fen: &str) -> String {
+    match parse_fen(fen) {
+        Err(FenError::Position(reason)) => reason,
+        Err(error) => panic!("{}: expected a position error, got {}", fen, error),
+        Ok(_) => panic!("{}: accepted", fen),
+    }
+}
+
+#[test]
+fn round_trips_through_to_fen() {
+    for fen in [
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 3 17",
+        "8/5k2/3p4/1p1Pp2p/pP2Pp1P/P4P1K/8/8 b - - 99 50",
+    ] {
+        assert_eq!(to_fen(&parse_fen(fen).unwrap()), fen);
+    }
+}
+
+#[test]
+fn reads_the_fields() {
+    let position = parse_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 12 40").unwrap();
+
+    assert_eq!(position.board.side_to_move(), Color::Black);
+    assert_eq!(position.board.piece_on(Square::E2), Some(Piece::Pawn));
+    assert_eq!(position.halfmove_clock, 12);
+    assert_eq!(position.fullmove_number, 40);
+}
+
+#[test]
+fn move_counters_are_optional() {
+    let position = parse_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -").unwrap();
+
+    assert_eq!(position.board, Board::default());
+    assert_eq!((position.halfmove_clock, position.fullmove_number), (0, 1));
+}
+
+#[test]
+fn make_move_keeps_the_counters() {
+    let mut position = parse_fen("4k3/8/8/8/8/8/4P3/R3K3 w - - 5 10").unwrap();
+
+    position.make_move(ChessMove::new(Square::A1, Square::A7, None));
+    assert_eq!((position.halfmove_clock, position.fullmove_number), (6, 10));
+
+    position.make_move(ChessMove::new(Square::E8, Square::D8, None));
+    assert_eq!((position.halfmove_clock, position.fullmove_number), (7, 11));
+
+    position.make_move(ChessMove::new(Square::E2, Square::E4, None));
+    assert_eq!((position.halfmove_clock, position.fullmove_number), (0, 11));
+}
+
+#[test]
+fn rejects_malformed_fields() {
+    assert!(matches!(parse_fen(""), Err(FenError::FieldCount(0))));
+    assert!(matches!(parse_fen("4k3/8/8/8/8/8/8/4K3 w - - 0"), Err(FenError::FieldCount(5))));
+    assert!(matches!(parse_fen("4k3/8/8/8/8/8/8/4K3 w - - x 1"), Err(FenError::Counter(_))));
+    assert!(matches!(parse_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 0"), Err(FenError::Counter(_))));
+    assert!(matches!(parse_fen("4k3/8/8/8/8/8/8/4K3 x - - 0 1"), Err(FenError::Position(_))));
+}
+
+#[test]
+fn rejects_a_board_of_the_wrong_size() {
+    assert_eq!(position_error("4k3/8/8/8/8/8/4K3 w - - 0 1"), "the board has 7 ranks instead of 8");
+    assert_eq!(position_error("4k3/8/8/8/8/8/8/4K4 w - - 0 1"), "rank 1 has 9 squares instead of 8");
+    assert_eq!(position_error("4k2/8/8/8/8/8/8/4K3 w - - 0 1"), "rank 8 has 7 squares instead of 8");
+}
+
+#[test]
+fn rejects_impossible_positions() {
+    assert_eq!(position_error("8/8/8/8/8/8/8/4K3 w - - 0 1"), "Black has 0 kings");
+    assert_eq!(position_error("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"), "White has 2 kings");
+    assert_eq!(position_error("P3k3/8/8/8/8/8/8/4K3 w - - 0 1"), "White has a pawn on a8");
+    assert_eq!(position_error("4k3/8/8/8/8/8/8/p3K3 w - - 0 1"), "Black has a pawn on a1");
+    assert_eq!(position_error("4k3/pppppppp/pppppppp/8/8/8/8/4K3 w - - 0 1"), "Black has 16 pawns");
+    assert_eq!(position_error("4k3/8/8/8/8/N7/PPPPPPPP/NNNNKNNN w - - 0 1"), "White has 17 pieces");
+    assert!(parse_fen("4k3/8/8/8/8/8/PPPPPPPP/NNNNKNNN w - - 0 1").is_ok());
+    assert_eq!(position_error("4k3/8/8/8/8/8/4R3/4K3 w - - 0 1"), "Black is in check with White to move");
+    assert_eq!(position_error("4k3/8/8/8/8/8/3p4/4K3 b - - 0 1"), "White is in check with Black to move");
+}
+
+#[test]
+fn rejects_castling_rights_without_the_king_or_rook() {
+    assert_eq!(position_error("4k3/8/8/8/8/8/8/4K3 w KQkq - 0 1"), "White can castle kingside without a rook on h1");
+    assert_eq!(position_error("r3k2r/8/8/8/8/8/8/R4K1R w Q - 0 1"), "White can castle queenside without a king on e1");
+    assert_eq!(position_error("r3k1r1/8/8/8/8/8/8/R3K2R w KQkq - 0 1"), "Black can castle kingside without a rook on h8");
+    assert!(parse_fen("r3k1r1/8/8/8/8/8/8/R3K2R w KQq - 0 1").is_ok());
+}
+
+#[test]
+fn rejects_impossible_en_passant_squares() {
+    assert_eq!(position_error("4k3/8/8/3pP3/8/8/8/4K3 w - e9 0 1"), "the en passant square e9 is not on the board");
+    assert_eq!(
+        position_error("4k3/8/8/8/4P3/8/8/4K3 w - e3 0 1"),
+        "the en passant square e3 is not possible with White to move"
+    );
+    assert_eq!(position_error("4k3/8/8/4P3/8/8/8/4K3 w - d6 0 1"), "the en passant square d6 has no Black pawn in front of it");
+    assert!(parse_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").is_ok());
+    assert!(parse_fen("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1").is_ok());
+}
@@ -0,0 +1,35 @@
+use std::str::FromStr;
+
+use chess::{Board, MoveGen};
+use rust_chess_engine::evaluation::{evaluate_board, evaluate_with_pawn_table};
+use rust_chess_engine::pawn_structure::PawnTable;
+
+const POSITIONS: [&str; 6] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "rnbqkb1r/pp1p1ppp/5n2/2pPp3/8/8/PPP1PPPP/RNBQKBNR w KQkq e6 0 4",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "4k3/8/8/8/8/8/P1P1P1P1/4K3 w - - 0 1",
+    "4k3/8/8/3P4/8/8/8/4K3 w - - 0 1",
+];
+
+// the search reads pawn structures from the table, so it must never see a stale one
+#[test]
+fn pawn_table_does_not_change_the_evaluation() {
+    let mut pawn_table = PawnTable::new();
+
+    for fen in POSITIONS {
+        let board = Board::from_str(fen).unwrap();
+        let mut boards = vec![board];
+
+        for first in MoveGen::new_legal(&board) {
+            let after_first = board.make_move_new(first);
+            boards.push(after_first);
+            boards.extend(MoveGen::new_legal(&after_first).map(|second| after_first.make_move_new(second)));
+        }
+
+        for board in boards {
+            assert_eq!(evaluate_with_pawn_table(&board, &mut pawn_table), evaluate_board(&board), "{}", board);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::str::FromStr;
+
+use chess::{Board, ChessMove, Piece, Square};
+use rust_chess_engine::pgn::{move_to_san, san_to_move};
+use rust_chess_engine::{parse_fen, parse_pgn, FenPosition, PgnError, PgnGame, PgnResult};
+
+fn board(fen: &str) -> Board {
+    Board::from_str(fen).unwrap()
+}
+
+fn san(fen: &str, source: Square, dest: Square, promotion: Option<Piece>) -> String {
+    move_to_san(&board(fen), ChessMove::new(source, dest, promotion))
+}
+
+// plays SAN moves from `start`, the way the gui records a game
+fn record(start: FenPosition, moves: &[&str]) -> PgnGame {
+    let mut game = PgnGame::new(start);
+    let mut position = start;
+
+    for san in moves {
+        let mv = san_to_move(&position.board, san).unwrap_or_else(|| panic!("{} is not legal", san));
+        game.push_move(&position.board, mv, None);
+        position.make_move(mv);
+    }
+
+    game
+}
+
+#[test]
+fn round_trips_a_game_through_to_pgn() {
+    let mut game = record(
+        FenPosition::default(),
+        &[
+            "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Bxc6", "dxc6", "O-O", "f6", "d4", "exd4", "Nxd4", "c5",
+            "Nb3", "Qxd1", "Rxd1", "Bg4", "f3", "Be6", "Nc3", "Kf7", "Bf4", "Bd6", "Bxd6", "cxd6",
+        ],
+    );
+    game.moves[4].comment = Some("the Exchange Variation".to_string());
+    game.moves[21].comment = Some("+0.35/5".to_string());
+    game.set_tag("White", "Human \"the bold\"");
+    game.set_tag("EngineDepth", "5");
+    game.set_result(PgnResult::Draw);
+
+    let text = game.to_pgn();
+    let parsed = parse_pgn(&text).unwrap();
+
+    assert_eq!(parsed.to_pgn(), text);
+    assert_eq!(parsed.moves.len(), game.moves.len());
+    for (parsed_move, played) in parsed.moves.iter().zip(&game.moves) {
+        assert_eq!(parsed_move.mv, played.mv);
+        assert_eq!(parsed_move.san, played.san);
+        assert_eq!(parsed_move.comment, played.comment);
+    }
+    assert_eq!(parsed.tag("White"), Some("Human \"the bold\""));
+    assert_eq!(parsed.tag("EngineDepth"), Some("5"));
+    assert_eq!(parsed.result, PgnResult::Draw);
+}
+
+#[test]
+fn round_trips_a_game_from_a_fen() {
+    let start = parse_fen("r3k2r/8/8/8/8/8/6p1/R3K2R b KQkq - 0 30").unwrap();
+    let mut game = record(start, &["gxh1=Q+", "Kd2", "O-O-O+", "Ke3"]);
+    game.set_result(PgnResult::Unfinished);
+
+    let text = game.to_pgn();
+    assert!(text.contains("[SetUp \"1\"]\n[FEN \"r3k2r/8/8/8/8/8/6p1/R3K2R b KQkq - 0 30\"]"));
+    assert!(text.contains("30... gxh1=Q+ 31. Kd2 O-O-O+ 32. Ke3 *"));
+
+    let parsed = parse_pgn(&text).unwrap();
+    assert_eq!(parsed.to_pgn(), text);
+    assert_eq!(parsed.start.fullmove_number, 30);
+}
+
+#[test]
+fn disambiguates_by_file_rank_or_both() {
+    // knights on b1 and f3 both reach d2
+    assert_eq!(san("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", Square::B1, Square::D2, None), "Nbd2");
+    assert_eq!(san("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1", Square::F3, Square::D2, None), "Nfd2");
+    // rooks on a1 and a5 both reach a3
+    assert_eq!(san("4k3/8/8/R7/8/8/8/R3K3 w - - 0 1", Square::A1, Square::A3, None), "R1a3");
+    // queens on e4, h4 and h1 all reach e1
+    assert_eq!(san("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", Square::H4, Square::E1, None), "Qh4e1");
+    assert_eq!(san("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", Square::E4, Square::E1, None), "Qee1");
+    assert_eq!(san("1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1", Square::H1, Square::E1, None), "Q1e1");
+    // a pinned knight can't move, so there is nothing to tell apart
+    assert_eq!(san("4k3/4r3/8/8/8/2N5/4N3/4K3 w - - 0 1", Square::C3, Square::B5, None), "Nb5");
+}
+
+#[test]
+fn reads_disambiguated_moves() {
+    let fen = "1k6/8/8/8/4Q2Q/8/8/K6Q w - - 0 1";
+    assert_eq!(san_to_move(&board(fen), "Qh4e1"), Some(ChessMove::new(Square::H4, Square::E1, None)));
+    assert_eq!(san_to_move(&board(fen), "Qh4-e1"), Some(ChessMove::new(Square::H4, Square::E1, None)));
+    assert_eq!(san_to_move(&board(fen), "Qee1"), Some(ChessMove::new(Square::E4, Square::E1, None)));
+    assert_eq!(san_to_move(&board(fen), "Q1e1"), Some(ChessMove::new(Square::H1, Square::E1, None)));
+    // not enough to tell the three queens apart
+    assert_eq!(san_to_move(&board(fen), "Qe1"), None);
+    assert_eq!(san_to_move(&board(fen), "Qhe1"), None);
+}
+
+#[test]
+fn writes_and_reads_castling() {
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    assert_eq!(san(fen, Square::E1, Square::G1, None), "O-O");
+    assert_eq!(san(fen, Square::E1, Square::C1, None), "O-O-O");
+
+    for (text, dest) in [("O-O", Square::G1), ("0-0", Square::G1), ("O-O-O", Square::C1), ("0-0-0+", Square::C1)] {
+        assert_eq!(san_to_move(&board(fen), text), Some(ChessMove::new(Square::E1, dest, None)), "{}", text);
+    }
+
+    // no castling through an attacked square
+    assert_eq!(san_to_move(&board("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1"), "O-O"), None);
+}
+
+#[test]
+fn writes_captures_promotions_checks_and_mates() {
+    assert_eq!(san("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", Square::E5, Square::D6, None), "exd6");
+    assert_eq!(san("3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1", Square::E7, Square::D8, Some(Piece::Queen)), "exd8=Q+");
+    assert_eq!(san("3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1", Square::E7, Square::D8, Some(Piece::Knight)), "exd8=N");
+    assert_eq!(san("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1", Square::A1, Square::A8, None), "Ra8#");
+
+    let fen = "3rk3/4P3/8/8/8/8/8/4K3 w - - 0 1";
+    let underpromotion = Some(ChessMove::new(Square::E7, Square::D8, Some(Piece::Knight)));
+    assert_eq!(san_to_move(&board(fen), "exd8=N"), underpromotion);
+    assert_eq!(san_to_move(&board(fen), "exd8N"), underpromotion);
+    assert_eq!(san_to_move(&board(fen), "ed8N"), underpromotion);
+}
+
+#[test]
+fn skips_move_numbers_nags_and_variations() {
+    let text = "[Event \"Test\"]
+[Result \"1-0\"]
+
+1.e4 e5 2. Nf3 {a comment (with parentheses)} 2... Nc6 $1 3.Bb5!? (3. Bc4 {the Italian} Bc5
+(3... Nf6 ; a ) inside a line comment
+4. d3) 4. c3) a6 ; the rest of the line
+4. Ba4 1-0
+";
+
+    let game = parse_pgn(text).unwrap();
+    let sans: Vec<&str> = game.moves.iter().map(|pgn_move| pgn_move.san.as_str()).collect();
+
+    assert_eq!(sans, ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4"]);
+    assert_eq!(game.moves[2].comment.as_deref(), Some("a comment (with parentheses)"));
+    assert_eq!(game.moves[5].comment.as_deref(), Some("the rest of the line"));
+    assert_eq!(game.tag("Event"), Some("Test"));
+    assert_eq!(game.result, PgnResult::WhiteWins);
+}
+
+#[test]
+fn reports_bad_input() {
+    match parse_pgn("1. e4 e5 2. Ke3 *") {
+        Err(PgnError::IllegalMove { ply, san }) => assert_eq!((ply, san.as_str()), (2, "Ke3")),
+        _ => panic!("Ke3 accepted"),
+    }
+    // a rank that isn't on the board
+    match parse_pgn("1. N0f3 *") {
+        Err(PgnError::IllegalMove { ply, san }) => assert_eq!((ply, san.as_str()), (0, "N0f3")),
+        _ => panic!("N0f3 accepted"),
+    }
+    assert!(matches!(parse_pgn("[Event Test]\n\n1. e4 *"), Err(PgnError::Tag(_))));
+    assert!(matches!(parse_pgn("[FEN \"8/8/8/8/8/8/8/8 w - - 0 1\"]\n\n*"), Err(PgnError::Fen(_))));
+}
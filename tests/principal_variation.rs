@@ -0,0 +1,24 @@
+use std::sync::atomic::AtomicBool;
+
+use rust_chess_engine::{Engine, SearchLimits, DEFAULT_HASH_MB};
+
+// a transposition table hit inside the principal variation used to cut the line to one move
+#[test]
+fn pv_survives_transposition_table_cutoffs() {
+    let stop = AtomicBool::new(false);
+
+    for fen in [
+        "7k/8/8/8/8/8/8/K7 w - - 0 1",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        let mut engine = Engine::new(DEFAULT_HASH_MB);
+        engine.set_fen(fen).unwrap();
+
+        // the deeper first search leaves entries that cut off the shallower second one
+        engine.search(&SearchLimits::fixed_depth(6), &stop);
+        let result = engine.search(&SearchLimits::fixed_depth(4), &stop);
+
+        assert!(result.pv.len() >= 4, "{}: {:?}", fen, result.pv);
+        assert_eq!(result.best_move, result.pv.first().copied());
+    }
+}
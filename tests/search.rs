@@ -0,0 +1,76 @@
+use std::sync::atomic::AtomicBool;
+
+use chess::{ChessMove, Square};
+use rust_chess_engine::best_move::mate_distance;
+use rust_chess_engine::{Engine, FenPosition, SearchLimits, SearchOptions, SearchResult, DEFAULT_HASH_MB};
+
+const OPTIONS: SearchOptions = SearchOptions { pruning: true, move_ordering: true, contempt: 0 };
+
+fn search(fen: &str, options: SearchOptions, depth: u32) -> SearchResult {
+    let mut engine = Engine::new(DEFAULT_HASH_MB);
+    engine.set_options(options);
+    engine.set_fen(fen).unwrap();
+    engine.search(&SearchLimits::fixed_depth(depth), &AtomicBool::new(false))
+}
+
+// alpha-beta only saves nodes: turning it off must not change the answer
+#[test]
+fn pruning_does_not_change_the_result() {
+    for fen in [
+        "r1bqkbnr/pppp1ppp/2n5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R b KQkq - 3 3",
+        "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    ] {
+        let pruned = search(fen, OPTIONS, 3);
+        let full = search(fen, SearchOptions { pruning: false, move_ordering: false, contempt: 0 }, 3);
+
+        assert_eq!(pruned.score, full.score, "{}", fen);
+        assert_eq!(pruned.best_move, full.best_move, "{}", fen);
+    }
+}
+
+// after 1. Nf3 Nf6 2. Ng1, Black can go back to the starting position with Ng8
+#[test]
+fn repetition_is_scored_with_the_contempt() {
+    let mut position = FenPosition::default();
+    let mut history = Vec::new();
+    for (source, dest) in [(Square::G1, Square::F3), (Square::G8, Square::F6), (Square::F3, Square::G1)] {
+        history.push(position.board.get_hash());
+        position.make_move(ChessMove::new(source, dest, None));
+    }
+    let repeat = Some(ChessMove::new(Square::F6, Square::G8, None));
+
+    for contempt in [-100, 0, 100] {
+        let mut engine = Engine::new(DEFAULT_HASH_MB);
+        engine.set_options(SearchOptions { contempt, ..OPTIONS });
+        engine.set_game(position, &history);
+        let result = engine.search(&SearchLimits::fixed_depth(4), &AtomicBool::new(false));
+
+        // a negative contempt makes the draw worth more than the position to Black,
+        // +100 for Black being -100 in the result, which is White's score
+        if contempt < 0 {
+            assert_eq!(result.best_move, repeat);
+            assert_eq!(result.score, -100);
+        } else {
+            assert_ne!(result.best_move, repeat, "contempt {}", contempt);
+        }
+    }
+}
+
+#[test]
+fn fifty_move_rule_is_a_draw_in_the_search() {
+    // a rook up, but every move reaches the hundredth halfmove without a capture or a pawn move
+    let result = search("4k3/8/8/8/8/8/8/R3K3 w - - 99 80", OPTIONS, 4);
+    assert_eq!(result.score, 0);
+}
+
+#[test]
+fn finds_the_shortest_mate() {
+    // Rd8+ Rxd8 Rxd8#, for either side
+    let result = search("r5k1/5ppp/8/8/8/8/3R1PPP/3R2K1 w - - 0 1", OPTIONS, 4);
+    assert_eq!(result.best_move, Some(ChessMove::new(Square::D2, Square::D8, None)));
+    assert_eq!(mate_distance(result.score), Some(2));
+
+    let result = search("3r2k1/3r1ppp/8/8/8/8/5PPP/R5K1 b - - 0 1", OPTIONS, 4);
+    assert_eq!(result.best_move, Some(ChessMove::new(Square::D7, Square::D1, None)));
+    assert_eq!(mate_distance(result.score), Some(-2));
+}